@@ -0,0 +1,200 @@
+use crate::{Atom, Event, LispIter, Walker};
+
+/// One atom of a [`Tree`], linked to its neighbours by index.
+///
+/// Arenas are made of these; start one with [`Node::EMPTY`], as in
+/// `[Node::EMPTY; 64]`.
+#[derive(Clone, Debug)]
+pub struct Node<'s> {
+    atom: Atom<'s>,
+    parent: Option<usize>,
+    first_child: Option<usize>,
+    next_sibling: Option<usize>,
+}
+
+impl<'s> Node<'s> {
+    /// A placeholder node, to fill an arena with before [`parse_into`].
+    pub const EMPTY: Self = Node {
+        atom: Atom::Identifier(""),
+        parent: None,
+        first_child: None,
+        next_sibling: None,
+    };
+
+    /// The node's atom. A list, vector or map still holds its [`LispIter`], but its
+    /// elements are also child nodes.
+    pub fn atom(&self) -> &Atom<'s> {
+        &self.atom
+    }
+
+    /// Index of the enclosing list, or `None` at the top level.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Index of the first element of a list, or `None` for an empty list or another atom.
+    pub fn first_child(&self) -> Option<usize> {
+        self.first_child
+    }
+
+    /// Index of the next atom in the same list, or at the top level.
+    pub fn next_sibling(&self) -> Option<usize> {
+        self.next_sibling
+    }
+}
+
+/// Error returned by [`parse_into`]: the arena is too small.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArenaError {
+    /// Number of nodes the input needs.
+    pub needed: usize,
+}
+
+impl core::fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "arena too small, {} nodes needed", self.needed)
+    }
+}
+
+impl core::error::Error for ArenaError {}
+
+/// Every atom of an input, parsed into an arena by [`parse_into`].
+///
+/// Nodes are numbered in source order, each list before its elements, so the first
+/// top-level atom, if any, is node 0.
+#[derive(Clone, Copy, Debug)]
+pub struct Tree<'a, 's> {
+    nodes: &'a [Node<'s>],
+}
+
+impl<'a, 's> Tree<'a, 's> {
+    /// The number of nodes, i.e. of atoms at every depth.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The node at `index`. Panics if it's out of bounds, like slice indexing.
+    pub fn node(&self, index: usize) -> &'a Node<'s> {
+        &self.nodes[index]
+    }
+
+    /// The top-level atoms, by index.
+    pub fn roots(&self) -> Children<'a, 's> {
+        Children {
+            nodes: self.nodes,
+            next: (!self.nodes.is_empty()).then_some(0),
+        }
+    }
+
+    /// The elements of the list at `index`, by index.
+    pub fn children(&self, index: usize) -> Children<'a, 's> {
+        Children {
+            nodes: self.nodes,
+            next: self.nodes[index].first_child,
+        }
+    }
+}
+
+/// Iterator over the indices of sibling nodes, see [`Tree::children`].
+#[derive(Clone)]
+pub struct Children<'a, 's> {
+    nodes: &'a [Node<'s>],
+    next: Option<usize>,
+}
+
+impl Iterator for Children<'_, '_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.next?;
+        self.next = self.nodes[index].next_sibling;
+        Some(index)
+    }
+}
+
+/// Parses all of `input` into `arena`, giving random access to every atom without
+/// allocating.
+///
+/// Each atom, at any depth, takes one node. If there are more than fit, the error tells
+/// how many are needed, so the caller can retry with a bigger arena. The input is walked
+/// without recursion, so deep nesting is fine.
+///
+/// ```
+/// use lisp_iter::{parse_into, Node};
+///
+/// let mut arena = [Node::EMPTY; 8];
+/// let tree = parse_into("(define (id x) x)", &mut arena).unwrap();
+/// assert_eq!(tree.len(), 6);
+/// let params = tree.children(0).nth(1).unwrap();
+/// let names: Vec<_> = tree.children(params).map(|i| tree.node(i).atom().clone()).collect();
+/// assert!(names[0].eq_identifier("id") && names[1].eq_identifier("x"));
+/// assert_eq!(tree.node(params).parent(), Some(0));
+///
+/// let mut small = [Node::EMPTY; 5];
+/// assert_eq!(parse_into("(define (id x) x)", &mut small).unwrap_err().needed, 6);
+///
+/// // Each level holds a list and an identifier, and the innermost list one more.
+/// let deep = "(a ".repeat(2_000) + "b" + &")".repeat(2_000);
+/// let needed = 2 * 2_000 + 1;
+/// let mut arena = vec![Node::EMPTY; needed - 1];
+/// assert_eq!(parse_into(&deep, &mut arena).unwrap_err().needed, needed);
+/// let mut arena = vec![Node::EMPTY; needed];
+/// let tree = parse_into(&deep, &mut arena).unwrap();
+/// assert_eq!(tree.len(), needed);
+/// let innermost = tree.children(needed - 3).nth(1).unwrap();
+/// assert!(tree.node(innermost).atom().eq_identifier("b"));
+/// let depth = core::iter::successors(Some(innermost), |&i| tree.node(i).parent()).count();
+/// assert_eq!(depth, 2_001);
+/// ```
+pub fn parse_into<'s, 'a>(
+    input: &'s str,
+    arena: &'a mut [Node<'s>],
+) -> Result<Tree<'a, 's>, ArenaError> {
+    let mut walker = Walker::new(LispIter::new(input));
+    let mut len = 0;
+    let mut parent = None;
+    let mut previous = None;
+    while let Some(event) = walker.next_event() {
+        let (atom, enters) = match event {
+            Event::Enter(atom) => (atom, true),
+            Event::Leaf(atom) => (atom, false),
+            Event::Exit => {
+                if len <= arena.len() {
+                    previous = parent;
+                    parent = parent.and_then(|i: usize| arena[i].parent);
+                }
+                continue;
+            }
+        };
+        let index = len;
+        len += 1;
+        if len > arena.len() {
+            continue; // only counting from here on
+        }
+        arena[index] = Node {
+            atom,
+            parent,
+            first_child: None,
+            next_sibling: None,
+        };
+        match (previous, parent) {
+            (Some(previous), _) => arena[previous].next_sibling = Some(index),
+            (None, Some(parent)) => arena[parent].first_child = Some(index),
+            (None, None) => {}
+        }
+        match enters {
+            true => (parent, previous) = (Some(index), None),
+            false => previous = Some(index),
+        }
+    }
+    if len > arena.len() {
+        return Err(ArenaError { needed: len });
+    }
+    Ok(Tree {
+        nodes: &arena[..len],
+    })
+}
@@ -0,0 +1,280 @@
+use crate::{ArityError, Atom, ConvertError, LispIter, ParseError};
+
+/// Types that can be read from the elements of a list, without serde.
+///
+/// Implemented for the primitives [`Atom`] converts into, for [`Option`] and for arrays.
+/// Implement it for your own types by reading the list with [`Fields`] or the
+/// `LispIter::expect_*` methods:
+///
+/// ```
+/// use lisp_iter::{DecodeError, Fields, FromAtoms, LispIter};
+///
+/// struct Server<'a> {
+///     host: &'a str,
+///     port: u16,
+/// }
+///
+/// struct Config<'a> {
+///     retries: u32,
+///     timeout: f64,
+///     name: Option<&'a str>,
+///     server: Server<'a>,
+///     origin: [i64; 2],
+/// }
+///
+/// impl<'a> FromAtoms<'a> for Server<'a> {
+///     fn from_atoms(mut iter: LispIter<'a>) -> Result<Self, DecodeError<'a>> {
+///         iter.expect_identifier()?;
+///         let fields = Fields::new(iter)?;
+///         Ok(Server {
+///             host: fields.get("host")?,
+///             port: fields.get("port")?,
+///         })
+///     }
+/// }
+///
+/// impl<'a> FromAtoms<'a> for Config<'a> {
+///     fn from_atoms(mut iter: LispIter<'a>) -> Result<Self, DecodeError<'a>> {
+///         iter.expect_identifier()?;
+///         let fields = Fields::new(iter)?;
+///         Ok(Config {
+///             retries: fields.get("retries")?,
+///             timeout: fields.get("timeout")?,
+///             name: fields.get("name")?,
+///             server: fields.get("server")?,
+///             origin: fields.get("origin")?,
+///         })
+///     }
+/// }
+///
+/// let input = r#"(config :retries 3 :timeout 1.5
+///                        :server (server :host "example.com" :port 8080)
+///                        :origin (0 -1))"#;
+/// let config = Config::from_atom(LispIter::new(input).next().unwrap()).unwrap();
+/// assert_eq!((config.retries, config.timeout, config.name), (3, 1.5, None));
+/// assert_eq!((config.server.host, config.server.port), ("example.com", 8080));
+/// assert_eq!(config.origin, [0, -1]);
+///
+/// let input = "(config :retries 3 :timeout 1.5 :server (server :host x :port 80800))";
+/// let err = Config::from_atom(LispIter::new(input).next().unwrap()).err().unwrap();
+/// assert_eq!(err.to_string(), ":port: value 80800 out of range for u16");
+/// ```
+pub trait FromAtoms<'a>: Sized {
+    /// Reads a value from the elements of a list.
+    fn from_atoms(iter: LispIter<'a>) -> Result<Self, DecodeError<'a>>;
+
+    /// Reads a value from a single atom.
+    ///
+    /// The default decodes the elements of a list with [`FromAtoms::from_atoms`] and
+    /// rejects any other atom. Primitives override it to convert the atom itself.
+    fn from_atom(atom: Atom<'a>) -> Result<Self, DecodeError<'a>> {
+        match atom {
+            Atom::List(iter) => Self::from_atoms(iter),
+            atom => Err(DecodeError::WrongType {
+                key: None,
+                error: ConvertError::WrongKind {
+                    found: atom.kind(),
+                    target: core::any::type_name::<Self>(),
+                },
+            }),
+        }
+    }
+
+    /// The value of a field that's absent, see [`Fields::get`].
+    ///
+    /// The default fails with [`DecodeError::MissingKey`], making fields required.
+    fn missing(key: &'a str) -> Result<Self, DecodeError<'a>> {
+        Err(DecodeError::MissingKey { key })
+    }
+}
+
+macro_rules! impl_from_atoms {
+    ($($t:ty),*) => {$(
+        /// Converts a single atom with `TryFrom`. A list must hold exactly that atom.
+        impl<'a> FromAtoms<'a> for $t {
+            fn from_atoms(iter: LispIter<'a>) -> Result<Self, DecodeError<'a>> {
+                let [atom] = Atom::List(iter).try_into_array()?;
+                Self::from_atom(atom)
+            }
+
+            fn from_atom(atom: Atom<'a>) -> Result<Self, DecodeError<'a>> {
+                Ok(Self::try_from(atom)?)
+            }
+        }
+    )*};
+}
+
+impl_from_atoms!(i64, i32, u32, u16, u8, f64, f32, &'a str);
+
+/// Takes the atom as it is. A list must hold exactly one atom.
+impl<'a> FromAtoms<'a> for Atom<'a> {
+    fn from_atoms(iter: LispIter<'a>) -> Result<Self, DecodeError<'a>> {
+        let [atom] = Atom::List(iter).try_into_array()?;
+        Ok(atom)
+    }
+
+    fn from_atom(atom: Atom<'a>) -> Result<Self, DecodeError<'a>> {
+        Ok(atom)
+    }
+}
+
+/// Makes a field optional: a missing one reads as `None`.
+impl<'a, T: FromAtoms<'a>> FromAtoms<'a> for Option<T> {
+    fn from_atoms(iter: LispIter<'a>) -> Result<Self, DecodeError<'a>> {
+        T::from_atoms(iter).map(Some)
+    }
+
+    fn from_atom(atom: Atom<'a>) -> Result<Self, DecodeError<'a>> {
+        T::from_atom(atom).map(Some)
+    }
+
+    fn missing(_key: &'a str) -> Result<Self, DecodeError<'a>> {
+        Ok(None)
+    }
+}
+
+/// Reads a list of exactly `N` elements, each with `T::from_atom`.
+impl<'a, T: FromAtoms<'a>, const N: usize> FromAtoms<'a> for [T; N] {
+    fn from_atoms(iter: LispIter<'a>) -> Result<Self, DecodeError<'a>> {
+        let atoms: [Atom<'a>; N] = Atom::List(iter).try_into_array()?;
+        let mut results = atoms.into_iter().map(T::from_atom);
+        let mut error = None;
+        let values: [Option<T>; N] = core::array::from_fn(|_| match results.next()? {
+            Ok(value) => Some(value),
+            Err(e) => {
+                error.get_or_insert(e);
+                None
+            }
+        });
+        match error {
+            Some(error) => Err(error),
+            None => Ok(values.map(|value| value.expect("every element decoded"))),
+        }
+    }
+}
+
+/// The `:keyword value` fields of a list, for implementing [`FromAtoms`].
+///
+/// Fields can be looked up in any order; each lookup scans the list again. Fields that are
+/// never looked up are ignored.
+#[derive(Clone)]
+pub struct Fields<'a> {
+    iter: LispIter<'a>,
+}
+
+impl<'a> Fields<'a> {
+    /// Checks that the remaining atoms of `iter` form a property list, as with
+    /// [`LispIter::plist`].
+    pub fn new(iter: LispIter<'a>) -> Result<Self, DecodeError<'a>> {
+        for pair in iter.clone().plist() {
+            pair?;
+        }
+        Ok(Fields { iter })
+    }
+
+    /// Decodes the value of the field `key`, given without its `:`.
+    ///
+    /// A missing field is up to `T`, see [`FromAtoms::missing`]; with `Option<T>` it's
+    /// `None`, otherwise an error. A key without a value, or given twice, is an error, and
+    /// conversion errors are tagged with `key`.
+    pub fn get<T: FromAtoms<'a>>(&self, key: &'a str) -> Result<T, DecodeError<'a>> {
+        let mut matches = self
+            .iter
+            .clone()
+            .plist()
+            .filter_map(Result::ok)
+            .filter(|(name, _)| name.eq_quote(key))
+            .map(|(_, value)| value);
+        let value = match (matches.next(), matches.next()) {
+            (None, _) => return T::missing(key),
+            (Some(_), Some(_)) => return Err(DecodeError::DuplicateKey { key }),
+            (Some(None), None) => return Err(DecodeError::MissingValue { key }),
+            (Some(Some(value)), None) => value,
+        };
+        T::from_atom(value).map_err(|error| error.with_key(key))
+    }
+}
+
+/// Error reading a value with [`FromAtoms`].
+///
+/// Errors about a field carry its key, without the `:`. For nested values it's the key
+/// of the innermost field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError<'a> {
+    /// The input is malformed, or a list of fields has a non-keyword where a key belongs.
+    Parse(ParseError<'a>),
+    /// A required field is absent.
+    MissingKey { key: &'a str },
+    /// A field's key is followed by another key or the end of the list.
+    MissingValue { key: &'a str },
+    /// A field is given more than once.
+    DuplicateKey { key: &'a str },
+    /// An atom doesn't convert to the type asked for.
+    WrongType {
+        key: Option<&'a str>,
+        error: ConvertError,
+    },
+    /// A list has the wrong number of elements.
+    WrongLength {
+        key: Option<&'a str>,
+        error: ArityError,
+    },
+}
+
+impl<'a> DecodeError<'a> {
+    /// Attributes an error not yet tied to a field to `key`.
+    fn with_key(self, key: &'a str) -> Self {
+        match self {
+            Self::WrongType { key: None, error } => Self::WrongType {
+                key: Some(key),
+                error,
+            },
+            Self::WrongLength { key: None, error } => Self::WrongLength {
+                key: Some(key),
+                error,
+            },
+            error => error,
+        }
+    }
+}
+
+impl<'a> From<ParseError<'a>> for DecodeError<'a> {
+    fn from(error: ParseError<'a>) -> Self {
+        Self::Parse(error)
+    }
+}
+
+impl From<ConvertError> for DecodeError<'_> {
+    fn from(error: ConvertError) -> Self {
+        Self::WrongType { key: None, error }
+    }
+}
+
+impl From<ArityError> for DecodeError<'_> {
+    fn from(error: ArityError) -> Self {
+        Self::WrongLength { key: None, error }
+    }
+}
+
+impl core::fmt::Display for DecodeError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "{}", error),
+            Self::MissingKey { key } => write!(f, "missing field :{}", key),
+            Self::MissingValue { key } => write!(f, "no value for field :{}", key),
+            Self::DuplicateKey { key } => write!(f, "field :{} given more than once", key),
+            Self::WrongType {
+                key: Some(key),
+                error,
+            } => write!(f, ":{}: {}", key, error),
+            Self::WrongLength {
+                key: Some(key),
+                error,
+            } => write!(f, ":{}: {}", key, error),
+            Self::WrongType { key: None, error } => write!(f, "{}", error),
+            Self::WrongLength { key: None, error } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError<'_> {}
@@ -0,0 +1,127 @@
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+
+use crate::OwnedAtom;
+
+/// Reads an s-expression embedded in another format. Requires the `serde` feature.
+///
+/// A string is parsed as an s-expression, as with `str::parse`; a parse error becomes a
+/// custom error carrying its message and offset. Other values map onto atoms
+/// structurally: integers and floats onto numbers, sequences onto [`OwnedAtom::List`],
+/// maps onto [`OwnedAtom::Map`] and byte arrays onto [`OwnedAtom::Bytes`], and strings
+/// inside them onto [`OwnedAtom::Quote`].
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use lisp_iter::OwnedAtom;
+///
+/// let json = r#"{"rule": "(when (> x 1) (print \"big\"))", "args": [1, 2.5, "x"]}"#;
+/// let config: BTreeMap<String, OwnedAtom> = serde_json::from_str(json).unwrap();
+/// assert_eq!(config["rule"], "(when (> x 1) (print \"big\"))".parse().unwrap());
+/// assert_eq!(config["args"], "(1 2.5 \"x\")".parse().unwrap());
+///
+/// let err = serde_json::from_str::<OwnedAtom>(r#""(a (b)""#).unwrap_err();
+/// assert_eq!(err.to_string(), "unclosed list at byte 0 at line 1 column 8");
+/// ```
+///
+/// Byte arrays are hex-encoded in uppercase, so they print and parse back like any
+/// `#"..."` literal:
+///
+/// ```
+/// use lisp_iter::OwnedAtom;
+/// use serde::de::{value::{BytesDeserializer, Error}, Deserialize};
+///
+/// let bytes = BytesDeserializer::<Error>::new(&[0xCA, 0xFE, 0x01]);
+/// let atom = OwnedAtom::deserialize(bytes).unwrap();
+/// assert_eq!(atom.to_string(), "#\"CAFE01\"");
+/// assert_eq!(atom.to_string().parse::<OwnedAtom>().unwrap(), atom);
+/// assert_eq!(atom, "#\"CAFE01\"".parse().unwrap());
+/// ```
+impl<'de> de::Deserialize<'de> for OwnedAtom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(AtomVisitor { nested: false })
+    }
+}
+
+/// Builds an [`OwnedAtom`], parsing strings unless `nested` inside a sequence or map.
+#[derive(Clone, Copy)]
+struct AtomVisitor {
+    nested: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for AtomVisitor {
+    type Value = OwnedAtom;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<OwnedAtom, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for AtomVisitor {
+    type Value = OwnedAtom;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.nested {
+            false => f.write_str("an s-expression string, a number, a sequence or a map"),
+            true => f.write_str("a string, a number, a sequence or a map"),
+        }
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<OwnedAtom, E> {
+        Ok(OwnedAtom::Integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<OwnedAtom, E> {
+        match i64::try_from(v) {
+            Ok(v) => Ok(OwnedAtom::Integer(v)),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Unsigned(v), &"an i64")),
+        }
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<OwnedAtom, E> {
+        Ok(OwnedAtom::Float32(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<OwnedAtom, E> {
+        Ok(OwnedAtom::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<OwnedAtom, E> {
+        match self.nested {
+            false => v.parse().map_err(E::custom),
+            true => Ok(OwnedAtom::Quote(String::from(v))),
+        }
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<OwnedAtom, E> {
+        const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+        let hex = v.iter().flat_map(|byte| {
+            [
+                DIGITS[usize::from(byte >> 4)],
+                DIGITS[usize::from(byte & 15)],
+            ]
+        });
+        Ok(OwnedAtom::Bytes(hex.collect()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<OwnedAtom, A::Error> {
+        let nested = AtomVisitor { nested: true };
+        let mut atoms = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(atom) = seq.next_element_seed(nested)? {
+            atoms.push(atom);
+        }
+        Ok(OwnedAtom::List(atoms))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OwnedAtom, A::Error> {
+        let nested = AtomVisitor { nested: true };
+        let mut atoms = Vec::with_capacity(map.size_hint().unwrap_or(0) * 2);
+        while let Some((key, value)) = map.next_entry_seed(nested, nested)? {
+            atoms.push(key);
+            atoms.push(value);
+        }
+        Ok(OwnedAtom::Map(atoms))
+    }
+}
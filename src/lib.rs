@@ -1,5 +1,20 @@
 #![no_std]
-use core::{fmt::Debug, iter::Chain, str::Chars};
+use core::{cmp::Ordering, fmt::Debug, ops::Range, str::Chars};
+
+mod arena;
+pub use arena::{parse_into, ArenaError, Children, Node, Tree};
+mod decode;
+pub use decode::{DecodeError, Fields, FromAtoms};
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+mod owned;
+#[cfg(feature = "alloc")]
+pub use owned::{GenericLispIter, OwnedAtom, ParseOwnedError, Windows};
+#[cfg(feature = "serde")]
+mod deserialize;
 
 #[derive(Clone)]
 struct CharByteIter<T>
@@ -10,7 +25,7 @@ where
     byte: usize,
 }
 
-impl<'a, T> Iterator for CharByteIter<T>
+impl<T> Iterator for CharByteIter<T>
 where
     T: Iterator<Item = char>,
 {
@@ -20,185 +35,5930 @@ where
         let c = self.chars.next()?;
         let ret = Some((self.byte, c));
         self.byte += c.len_utf8();
-        return ret;
+        ret
+    }
+}
+
+impl<T> CharByteIter<T>
+where
+    T: Iterator<Item = char> + Clone,
+{
+    fn peek(&self) -> Option<(usize, char)> {
+        self.chars.clone().next().map(|c| (self.byte, c))
+    }
+}
+
+/// Lexical class of a token, as seen by [`LispIter::lex`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Token {
+    Open(Bracket),
+    Close(Bracket),
+    /// A string, `raw` holding the number of `#`s for an `r"..."` or `r#"..."#` one.
+    Quote {
+        raw: Option<usize>,
+        closed: bool,
+    },
+    /// A `#"..."` hex byte string.
+    Bytes {
+        closed: bool,
+    },
+    Word,
+    LineComment,
+    BlockComment {
+        closed: bool,
+    },
+    /// The `'` of a quote form, or with `head` set, the `'` starting the contents of one,
+    /// which reads as `quote`.
+    Apostrophe {
+        head: bool,
+    },
+}
+
+/// Kind of bracket of a [`Token::Open`] or [`Token::Close`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Bracket {
+    /// `( )`, around an [`Atom::List`].
+    Paren,
+    /// `[ ]`, around an [`Atom::Vector`].
+    Square,
+    /// `{ }`, around an [`Atom::Map`].
+    Curly,
+}
+
+impl Bracket {
+    /// The char closing a bracket of this kind.
+    fn closer(self) -> char {
+        match self {
+            Bracket::Paren => ')',
+            Bracket::Square => ']',
+            Bracket::Curly => '}',
+        }
     }
 }
 
+/// Chars that end an unquoted word.
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | '"' | ';')
+}
+
 ///
 /// Iterator over a lisp expression provided by the input.
-/// 
+///
 /// [`LispIter::next`] returns an [`Atom`]
-/// 
+///
+/// Cloning is O(1): a clone copies the borrowed input, the position within it and a few
+/// flags, and nothing is shared between the two afterwards. That makes clones the way to
+/// look ahead, parse speculatively (see [`LispIter::fork`]) or go over the same input
+/// more than once. A clone resumes exactly where the original stood, and advancing
+/// either leaves the other alone, including the iterators of lists they return:
+///
+/// ```
+/// use lisp_iter::LispIter;
+///
+/// let mut original = LispIter::new("a (b c) d");
+/// original.next();
+/// let mut clone = original.clone();
+/// let mut list = clone.next().unwrap().into_list_iter().unwrap();
+/// assert!(clone.next().unwrap().eq_identifier("d"));
+///
+/// let mut list_clone = list.clone();
+/// assert!(list_clone.next().unwrap().eq_identifier("b"));
+/// assert!(list.next().unwrap().eq_identifier("b"));
+/// assert!(list_clone.next().unwrap().eq_identifier("c"));
+///
+/// let mut rest = original.next().unwrap().into_list_iter().unwrap();
+/// assert!(rest.next().unwrap().eq_identifier("b"));
+/// assert!(original.next().unwrap().eq_identifier("d"));
+/// ```
 #[derive(Clone)]
 pub struct LispIter<'s> {
+    /// The text being iterated over. For the iterator of a nested [`Atom::List`] this is
+    /// just the list's contents; see [`LispIter::base`] for where they start. A prefix or
+    /// suffix given to [`LispIter::new_prefixed`] isn't included.
     pub input: &'s str,
-    chars: CharByteIter<Chain<Chars<'s>, core::option::IntoIter<char>>>,
+    /// Text lexed before and after `input`, see [`LispIter::new_prefixed`].
+    prefix: &'s str,
+    suffix: &'s str,
+    /// The rest of the piece of the lexed text being lexed, see [`LispIter::lex_from`].
+    chars: CharByteIter<Chars<'s>>,
+    /// Whether the `\n` after that piece is still to come.
+    newline: bool,
+    config: ParseConfig,
+    base: usize,
+    /// Offset into `input` where the last step of `advance` started, for `unget`.
+    last: Option<usize>,
+    /// Whether this iterates a `'` quote form, whose `input` starts with the `'`.
+    quoted: bool,
 }
 
 impl<'s> LispIter<'s> {
     pub fn new(input: &'s str) -> LispIter<'s> {
+        Self::with_config(input, ParseConfig::default())
+    }
+
+    /// Creates an iterator using the given [`ParseConfig`]. Nested lists inherit it.
+    pub fn with_config(input: &'s str, config: ParseConfig) -> LispIter<'s> {
         LispIter {
             input,
+            prefix: "",
+            suffix: "",
             chars: CharByteIter {
-                chars: input.chars().chain(Some('\n')),
+                chars: input.chars(),
                 byte: 0,
             },
+            newline: true,
+            config,
+            base: 0,
+            last: None,
+            quoted: false,
         }
     }
-}
 
-#[derive(Clone)]
-pub enum Atom<'a> {
-    /// Any unquoted word seperated by whitespaces or bound by a list.
-    Identifier(&'a str),
-    
-    /// Any string between two " "
-    /// 
-    /// Note: quotes are unescaped i.e. \n \r and other escape sequences aren't taken into account.
-    /// This is to prevent dynamic heap allocations.
-    Quote(&'a str),
+    /// Creates an iterator over `input` as a region starting `base` bytes into some larger
+    /// document.
+    ///
+    /// Spans and error offsets produced by the iterator and its nested lists are then
+    /// relative to that document, so `input` can be sliced out of a Markdown code block or
+    /// a template without diagnostics pointing at the wrong place.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, ParseError};
+    ///
+    /// let document = "Example:\n```\n(a (b \"c)\n```";
+    /// let start = document.find('(').unwrap();
+    /// let end = document.rfind("\n```").unwrap();
+    /// let mut iter = LispIter::with_offset(&document[start..end], start);
+    /// assert_eq!(iter.base(), 13);
+    /// let mut list = iter.next_spanned().unwrap().0.into_list_iter().unwrap();
+    /// assert_eq!(list.next_spanned().unwrap().1.outer, 14..15);
+    /// let error = list.try_next().unwrap().unwrap_err();
+    /// assert_eq!(error, ParseError::UnclosedList { offset: 16 });
+    /// assert_eq!(&document[16..17], "(");
+    /// ```
+    pub fn with_offset(input: &'s str, base: usize) -> LispIter<'s> {
+        LispIter {
+            base,
+            ..LispIter::new(input)
+        }
+    }
 
-    /// Signed 64-bit integer.
-    Integer(i64),
+    /// Creates an iterator that resumes parsing `input` at byte `offset`, e.g. after a
+    /// hand-written parser has consumed a header.
+    ///
+    /// Unlike `LispIter::new(&input[offset..])`, spans, error offsets and
+    /// [`LispIter::byte_position`] stay relative to the start of `input`. This is
+    /// [`LispIter::with_offset`] applied to the remainder, so [`LispIter::input`] only holds
+    /// `&input[offset..]`.
+    ///
+    /// Panics if `offset` is out of bounds or not on a char boundary, like slicing would.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let input = "v2;(a b) c";
+    /// let header = input.find(';').unwrap() + 1;
+    /// let mut iter = LispIter::new_with_offset(input, header);
+    /// assert_eq!(iter.byte_position(), 3);
+    /// assert_eq!(iter.next_spanned().unwrap().1.outer, 3..8);
+    /// assert_eq!(iter.byte_position(), 8);
+    /// assert_eq!(iter.input, "(a b) c");
+    /// ```
+    pub fn new_with_offset(input: &'s str, offset: usize) -> LispIter<'s> {
+        Self::with_offset(&input[offset..], offset)
+    }
 
-    /// 64-bit floating-point number.
-    Float(f64),
+    /// Creates an iterator over `input` as if `prefix` came before it and `suffix` after,
+    /// e.g. to read a script as one implicit `(begin ...)` form without copying it.
+    ///
+    /// Atoms can come from any of the three strings, and a list may start in one and end
+    /// in another. Spans and error offsets stay relative to `input` alone: anything from
+    /// `prefix` is reported at the start of `input` and anything from `suffix` at its end.
+    /// [`LispIter::input`], [`LispIter::remainder`] and [`LispIter::byte_position`] only
+    /// cover `input` as well.
+    ///
+    /// The three are lexed as if on separate lines, so a word or a `;` comment ends with
+    /// the string it's in. `prefix` and `suffix` should hold whole tokens otherwise: a
+    /// string left open in `prefix` still runs on into `input`, but its text is cut at the
+    /// end of `prefix`.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let script = "(define x 1) (print x)";
+    /// let mut iter = LispIter::new_prefixed("(begin ", script, ")");
+    /// let (body, span) = iter.next_spanned().unwrap();
+    /// assert_eq!(span.outer, 0..script.len());
+    /// assert!(iter.next().is_none());
+    ///
+    /// let mut forms = body.into_list_iter().unwrap();
+    /// let (head, span) = forms.next_spanned().unwrap();
+    /// assert!(head.eq_identifier("begin"));
+    /// assert_eq!(span.outer, 0..0);
+    /// assert_eq!(forms.next_spanned().unwrap().1.outer, 0..12);
+    /// assert_eq!(forms.next_sourced().unwrap().1, "(print x)");
+    /// assert!(forms.next().is_none());
+    /// ```
+    pub fn new_prefixed(prefix: &'s str, input: &'s str, suffix: &'s str) -> LispIter<'s> {
+        LispIter::new(input).affixed(prefix, suffix)
+    }
 
-    /// Anything between two ( )
-    /// 
-    /// Holds another [`LispIter`]
-    List(LispIter<'a>),
-}
+    /// Byte offset of [`LispIter::input`] within the outermost input.
+    ///
+    /// It's 0 for an iterator created with [`LispIter::new`], or the given base for
+    /// [`LispIter::with_offset`]. Spans and error offsets are always reported relative to
+    /// the outermost input, i.e. with this offset already added.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter, ParseError};
+    ///
+    /// let mut iter = LispIter::new("(a (b 1x) \"c)");
+    /// let Some(Atom::List(mut outer)) = iter.next() else { panic!() };
+    /// assert_eq!((outer.input, outer.base()), ("a (b 1x) \"c)", 1));
+    /// outer.next();
+    /// let (inner, span) = outer.next_spanned().unwrap();
+    /// assert_eq!(span.outer, 3..9);
+    /// let Atom::List(inner) = inner else { panic!() };
+    /// assert_eq!(inner.base(), 4);
+    /// assert_eq!(outer.try_next().unwrap().unwrap_err(), ParseError::UnclosedQuote { offset: 10 });
+    /// ```
+    pub fn base(&self) -> usize {
+        self.base
+    }
 
-/// Helper iterator convenient for iterating over a [`Atom::List`]'s contence.
-/// 
-/// Can be constructed by calling `.into_iterator()` on any [`Atom`]
-///
-pub struct AtomIter<'a> {
-    atom: Option<Atom<'a>>,
-}
+    /// Creates the iterator for a nested list, from a range of the lexed text.
+    fn sublist(&self, range: Range<usize>) -> LispIter<'s> {
+        let (prefix_end, tail) = (self.prefix.len(), self.tail());
+        let suffix = |offset: usize| offset.saturating_sub(tail).min(self.suffix.len());
+        let input = &self.input[self.clamp(range.start)..self.clamp(range.end)];
+        LispIter {
+            base: self.pos(range.start),
+            ..LispIter::with_config(input, self.config).affixed(
+                &self.prefix[range.start.min(prefix_end)..range.end.min(prefix_end)],
+                &self.suffix[suffix(range.start)..suffix(range.end)],
+            )
+        }
+    }
 
-impl<'a> AtomIter<'a> {
-    pub fn new(atom: Atom<'a>) -> Self {
-        Self { atom: Some(atom) }
+    /// Puts `prefix` and `suffix` around the input of an iterator that hasn't moved yet.
+    fn affixed(self, prefix: &'s str, suffix: &'s str) -> LispIter<'s> {
+        if prefix.is_empty() && suffix.is_empty() {
+            return self;
+        }
+        let mut iter = LispIter {
+            prefix,
+            suffix,
+            ..self
+        };
+        iter.lex_from(0);
+        iter
     }
-}
 
-impl<'a> Iterator for AtomIter<'a> {
-    type Item = Atom<'a>;
+    /// Like [`Iterator::next`], but also returns the [`Span`] the atom was parsed from.
+    ///
+    /// [`Span::outer`] covers the atom's full source text: the quotes of a [`Atom::Quote`],
+    /// the `:` of a keyword and both parens of a [`Atom::List`]. Like all offsets, it's
+    /// relative to the outermost input, see [`LispIter::base`].
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter, Span};
+    ///
+    /// let mut iter = LispIter::new("(a \"b\") :key 12");
+    /// let (list, span) = iter.next_spanned().unwrap();
+    /// assert_eq!(span, Span { outer: 0..7, inner: 1..6 });
+    /// let Atom::List(mut list) = list else { panic!() };
+    /// list.next();
+    /// assert_eq!(list.next_spanned().unwrap().1, Span { outer: 3..6, inner: 4..5 });
+    /// assert_eq!(iter.next_spanned().unwrap().1, Span { outer: 8..12, inner: 9..12 });
+    /// assert_eq!(iter.next_spanned().unwrap().1, Span { outer: 13..15, inner: 13..15 });
+    /// assert!(iter.next_spanned().is_none());
+    /// ```
+    pub fn next_spanned(&mut self) -> Option<(Atom<'s>, Span)> {
+        self.next_ranged().map(|(atom, span, _)| (atom, span))
+    }
 
-    /// Returns either the contence of [`Atom::List`] or itself if it is not an [`Atom::List`]
-    fn next(&mut self) -> Option<Self::Item> {
-        match &mut self.atom {
-            Some(Atom::List(iter)) => iter.next(),
-            atom => atom.take(),
+    /// Like [`LispIter::next_spanned`], but also returns the atom's range of the lexed
+    /// text, which unlike its span tells apart atoms from a prefix or suffix.
+    fn next_ranged(&mut self) -> Option<(Atom<'s>, Span, Range<usize>)> {
+        loop {
+            let parsed = self.advance()?;
+            if let Some(atom) = parsed.atom {
+                return Some((atom, parsed.span, parsed.range));
+            }
         }
     }
-}
 
-impl<'a> IntoIterator for Atom<'a> {
-    type Item = Atom<'a>;
-    type IntoIter = AtomIter<'a>;
+    /// Like [`Iterator::next`], but also returns the atom's verbatim source text.
+    ///
+    /// That's the text of [`Span::outer`]: a quote with its quotes, a keyword with its `:`
+    /// and a list with both parens. An unclosed list or quote runs to the end of input.
+    /// Atoms themselves only carry their contents, so this is the way to echo back exactly
+    /// what was written.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new(r#"(a  "b") :c 1.50"#);
+    /// assert_eq!(iter.next_sourced().unwrap().1, r#"(a  "b")"#);
+    /// assert_eq!(iter.next_sourced().unwrap().1, ":c");
+    /// assert_eq!(iter.next_sourced().unwrap().1, "1.50");
+    /// ```
+    pub fn next_sourced(&mut self) -> Option<(Atom<'s>, &'s str)> {
+        let (atom, span) = self.next_spanned()?;
+        let source = &self.input[span.outer.start - self.base..span.outer.end - self.base];
+        Some((atom, source))
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        AtomIter::new(self)
+    /// Like [`LispIter::next_spanned`], but also returns the range of trivia skipped to get
+    /// to the atom.
+    ///
+    /// The trivia range runs from where the iterator stood, i.e. the end of the previous
+    /// atom or the start of the input, to the start of the atom. It holds whitespace and
+    /// comments, and anything the lenient parse skipped such as a stray `)`. Count the
+    /// `\n`s in it to find blank lines between forms.
+    ///
+    /// ```
+    /// let input = "a ; c\n\n (b) )x";
+    /// let mut iter = lisp_iter::LispIter::new(input);
+    /// let (_, span, trivia) = iter.next_with_trivia().unwrap();
+    /// assert_eq!((span.outer, trivia), (0..1, 0..0));
+    /// let (_, span, trivia) = iter.next_with_trivia().unwrap();
+    /// assert_eq!((span.outer, &input[trivia]), (8..11, " ; c\n\n "));
+    /// let (x, _, trivia) = iter.next_with_trivia().unwrap();
+    /// assert!(matches!(x, lisp_iter::Atom::Identifier("x")));
+    /// assert_eq!(&input[trivia], " )");
+    /// assert!(iter.next_with_trivia().is_none());
+    /// ```
+    pub fn next_with_trivia(&mut self) -> Option<(Atom<'s>, Span, Range<usize>)> {
+        let start = self.byte_position();
+        let (atom, span) = self.next_spanned()?;
+        let trivia = start..span.outer.start;
+        Some((atom, span, trivia))
+    }
+
+    /// Like [`Iterator::next`], but also returns the comments that belong to the atom, for
+    /// extracting documentation.
+    ///
+    /// The leading comments are the last run of comments before the atom, with no blank
+    /// line between them. Blank lines may separate the run from the atom. A comment on the
+    /// same line as the previous atom's end trails that atom instead, and never leads this
+    /// one. Anything else in between, like a stray `)`, ends a run.
+    ///
+    /// The trailing comment is one on the same line as the atom's end. It isn't consumed,
+    /// so it's still skipped over like any comment when the next atom is read.
+    ///
+    /// ```
+    /// let input = "(define a 1) ; old\n\n;; unrelated\n\n;;; Adds one.\n;;; Really.\n(define (inc x) (+ x 1)) ; trailing";
+    /// let mut iter = lisp_iter::LispIter::new(input);
+    /// let (_, comments) = iter.next_with_comments().unwrap();
+    /// assert_eq!((comments.leading, comments.trailing), ("", Some("; old")));
+    /// let (_, comments) = iter.next_with_comments().unwrap();
+    /// assert!(comments.leading_comments().eq([";;; Adds one.", ";;; Really."]));
+    /// assert_eq!(comments.trailing, Some("; trailing"));
+    /// ```
+    pub fn next_with_comments(&mut self) -> Option<(Atom<'s>, Comments<'s>)> {
+        let start = self.clamp(self.offset());
+        let (atom, span) = self.next_spanned()?;
+        let trivia = &self.input[start..span.outer.start - self.base];
+        let mut lexer = LispIter::with_config(trivia, self.config);
+        let mut run: Option<Range<usize>> = None;
+        let mut end = 0;
+        while let Some((token, range)) = lexer.lex() {
+            let newlines = trivia[end..range.start].matches('\n').count();
+            run = match token {
+                Token::LineComment | Token::BlockComment { .. } => match run {
+                    _ if end == 0 && start > 0 && newlines == 0 => None,
+                    Some(run) if newlines < 2 => Some(run.start..range.end),
+                    _ => Some(range.clone()),
+                },
+                _ => None,
+            };
+            end = range.end;
+        }
+
+        let mut after = self.clone();
+        let trailing = match after.lex() {
+            Some((Token::LineComment | Token::BlockComment { .. }, range))
+                if !self.text(self.offset()..range.start).contains('\n') =>
+            {
+                Some(self.text(range))
+            }
+            _ => None,
+        };
+        let comments = Comments {
+            leading: run.map_or("", |run| &trivia[run]),
+            trailing,
+            config: self.config,
+        };
+        Some((atom, comments))
+    }
+
+    /// Where the iterator currently stands, as a byte offset into the outermost input.
+    ///
+    /// That's the end of the last atom returned, or the start of the input before any.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new("(a b)  c ; d");
+    /// assert_eq!(iter.byte_position(), 0);
+    /// let mut list = iter.next().unwrap().into_list_iter().unwrap();
+    /// assert_eq!(iter.byte_position(), 5);
+    /// list.next();
+    /// assert_eq!(list.byte_position(), 2);
+    /// iter.next();
+    /// assert_eq!(iter.byte_position(), 8);
+    /// assert!(iter.next().is_none());
+    /// ```
+    pub fn byte_position(&self) -> usize {
+        self.pos(self.offset())
+    }
+
+    /// Pushes back the atom just returned, so that the next call to [`Iterator::next`]
+    /// returns it again.
+    ///
+    /// Useful in recursive descent, where the atom that picks a production belongs to the
+    /// sub-parser. A [`LispIter`] can't hold an [`Atom`], which may itself hold a
+    /// [`LispIter`], so rather than buffering `atom` this rewinds to where it started and
+    /// it's parsed again. Only one atom can be pushed back.
+    ///
+    /// Fails, leaving the iterator alone, if `atom` isn't the one just returned: if it's
+    /// another atom, or nothing was returned since the iterator was created or last ungot,
+    /// as with a second `unget` in a row. Lists, vectors and maps must be the very ones
+    /// returned, not just equal to them.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("1 (2) 3");
+    /// let one = iter.next().unwrap();
+    /// iter.unget(one.clone()).unwrap();
+    /// assert!(iter.unget(one).is_err());
+    /// assert!(iter.next().unwrap().eq_integer(1));
+    ///
+    /// let two = iter.next().unwrap();
+    /// let lookalike = lisp_iter::LispIter::new("(2)").next().unwrap();
+    /// assert_eq!(two, lookalike);
+    /// assert!(iter.unget(lookalike).is_err());
+    /// assert!(iter.unget(lisp_iter::LispIter::new("3").next().unwrap()).is_err());
+    /// iter.unget(two).unwrap();
+    /// assert!(iter.next().unwrap().is_list());
+    /// ```
+    pub fn unget(&mut self, atom: Atom<'s>) -> Result<(), UngetError<'s>> {
+        let Some(last) = self.last else {
+            return Err(UngetError { atom });
+        };
+        let mut fork = self.clone();
+        fork.seek(last);
+        match fork.next() {
+            Some(returned) if returned.is_same(&atom) => {
+                self.last = None;
+                self.seek(last);
+                Ok(())
+            }
+            _ => Err(UngetError { atom }),
+        }
+    }
+
+    /// Moves the iterator back to the start of its input, for another pass over it.
+    ///
+    /// Same as making a new iterator over [`LispIter::input`], but keeps the
+    /// [`ParseConfig`] and, for a nested list's iterator, the offsets relative to the
+    /// outermost input. For a list's iterator, that's the start of the list's contents.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("(define x 1) (print x)");
+    /// let lists = iter.by_ref().filter(|atom| atom.is_list()).count();
+    /// iter.rewind();
+    /// assert_eq!(iter.count(), lists);
+    /// ```
+    pub fn rewind(&mut self) {
+        self.last = None;
+        self.seek(0);
+    }
+
+    /// Returns the part of [`LispIter::input`] not consumed yet.
+    ///
+    /// It starts right after the last atom returned, or at the start of the input before
+    /// any: whitespace and comments after that atom are still part of it. For a nested
+    /// list's iterator it's a suffix of the list's contents, without the closing bracket.
+    ///
+    /// The rest of the input can be handed to another parser, or to a new [`LispIter`]
+    /// which picks up where this one left off. Use [`LispIter::new_with_offset`] with
+    /// [`LispIter::byte_position`] instead to keep spans relative to the whole input.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("(header v1) raw content");
+    /// iter.next();
+    /// assert_eq!(iter.remainder(), " raw content");
+    /// ```
+    pub fn remainder(&self) -> &'s str {
+        &self.input[self.clamp(self.offset())..]
+    }
+
+    /// Current offset into the lexed text, not counting the trailing sentinel.
+    fn offset(&self) -> usize {
+        self.chars.byte.min(self.end())
+    }
+
+    /// Offset of `input` into the lexed text, past the prefix and its `\n`.
+    fn lead(&self) -> usize {
+        match self.prefix.len() {
+            0 => 0,
+            len => len + 1,
+        }
+    }
+
+    /// Offset of the suffix into the lexed text, past `input` and its `\n`.
+    fn tail(&self) -> usize {
+        self.lead() + self.input.len() + 1
+    }
+
+    /// Length of the lexed text, not counting the trailing sentinel.
+    fn end(&self) -> usize {
+        match self.suffix.len() {
+            0 => self.lead() + self.input.len(),
+            len => self.tail() + len,
+        }
+    }
+
+    /// Turns an offset into the lexed text into one into `input`. Offsets into the prefix
+    /// or suffix end up at the start or end of `input`.
+    fn clamp(&self, offset: usize) -> usize {
+        offset.saturating_sub(self.lead()).min(self.input.len())
+    }
+
+    /// Turns an offset into the lexed text into one into the outermost input, like all
+    /// offsets reported.
+    fn pos(&self, offset: usize) -> usize {
+        self.base + self.clamp(offset)
+    }
+
+    /// The lexed text at `range`. A range running on from one piece into the next, as for
+    /// a string left open in the prefix, is cut at the end of the first.
+    fn text(&self, range: Range<usize>) -> &'s str {
+        let (lead, tail) = (self.lead(), self.tail());
+        let (piece, from) = match range.start {
+            start if start < lead => (self.prefix, 0),
+            start if start < tail => (self.input, lead),
+            _ => (self.suffix, tail),
+        };
+        let start = (range.start - from).min(piece.len());
+        &piece[start..(range.end - from).clamp(start, piece.len())]
+    }
+
+    /// Moves the lexer to `offset` into the lexed text.
+    ///
+    /// That's the prefix, `input` and the suffix, in that order, with offsets counting
+    /// from the start of the prefix. A `\n` separates each non-empty piece from the next,
+    /// so no token runs from one into another unnoticed, and `input` is always followed by
+    /// one, which serves as the sentinel that ends the last token. `chars` only runs over
+    /// the piece at `offset`; [`LispIter::next_piece`] moves on to the next.
+    fn lex_from(&mut self, offset: usize) {
+        let (lead, tail) = (self.lead(), self.tail());
+        let (text, newline, start) = match offset {
+            offset if offset < lead => (self.prefix, true, 0),
+            offset if offset < tail => (self.input, true, lead),
+            _ => (self.suffix, !self.suffix.is_empty(), tail),
+        };
+        let skip = offset - start;
+        self.chars = CharByteIter {
+            chars: text[skip.min(text.len())..].chars(),
+            byte: offset,
+        };
+        self.newline = newline && skip <= text.len();
+    }
+
+    /// Moves on from a piece of the lexed text `chars` has run out of, returning the `\n`
+    /// after it, if any. Without one, the whole text is lexed.
+    #[cold]
+    fn next_piece(&mut self) -> Option<(usize, char)> {
+        if !self.newline {
+            return None;
+        }
+        let byte = self.chars.byte;
+        self.lex_from(byte + 1);
+        Some((byte, '\n'))
+    }
+
+    /// The next char of the lexed text and its offset.
+    fn next_char(&mut self) -> Option<(usize, char)> {
+        match self.chars.next() {
+            Some(next) => Some(next),
+            None => self.next_piece(),
+        }
+    }
+
+    /// The next char of the lexed text and its offset, without consuming it.
+    fn peek_char(&self) -> Option<(usize, char)> {
+        let newline = self.newline.then_some((self.chars.byte, '\n'));
+        self.chars.peek().or(newline)
+    }
+
+    /// Consumes the next char only if it is `c`.
+    fn next_char_if(&mut self, c: char) -> bool {
+        let matches = self.peek_char().is_some_and(|(_, p)| p == c);
+        if matches {
+            self.next_char();
+        }
+        matches
+    }
+
+    /// Consumes chars up to and including the first one matching `predicate`.
+    fn find_char(&mut self, mut predicate: impl FnMut(char) -> bool) -> Option<(usize, char)> {
+        loop {
+            if let Some(found) = self.chars.find(|&(_, c)| predicate(c)) {
+                return Some(found);
+            }
+            let (byte, c) = self.next_piece()?;
+            if predicate(c) {
+                return Some((byte, c));
+            }
+        }
+    }
+
+    /// Like [`Iterator::next`], but reports malformed input as a [`ParseError`] instead of
+    /// recovering from it.
+    ///
+    /// Iteration can continue after an error, resuming right after the offending token.
+    /// Errors inside a list are reported by the list's own iterator once it is walked.
+    pub fn try_next(&mut self) -> Option<Result<Atom<'s>, ParseError<'s>>> {
+        let parsed = self.advance()?;
+        Some(match parsed.error {
+            Some(error) => Err(error),
+            None => Ok(parsed
+                .atom
+                .expect("a step without an error carries an atom")),
+        })
+    }
+
+    /// Creates an iterator that reports any malformed input as a [`ParseError`], however
+    /// deeply nested, instead of recovering from it.
+    ///
+    /// Malformed numbers are errors too, as with [`ParseConfig::strict_numbers`]. Plain
+    /// [`LispIter::new`] stays lenient.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, ParseError};
+    ///
+    /// let mut iter = LispIter::strict_mode("(ok 1) (bad (1x)) (open");
+    /// assert!(iter.next().unwrap().is_ok());
+    /// assert_eq!(iter.next().unwrap().unwrap_err(), ParseError::InvalidNumber { offset: 13, token: "1x" });
+    /// assert_eq!(iter.next().unwrap().unwrap_err(), ParseError::UnclosedList { offset: 18 });
+    /// assert!(iter.next().is_none());
+    /// ```
+    pub fn strict_mode(input: &'s str) -> StrictLispIter<'s> {
+        let config = ParseConfig {
+            strict_numbers: true,
+            ..ParseConfig::default()
+        };
+        StrictLispIter {
+            iter: LispIter::with_config(input, config),
+        }
     }
 }
 
-impl Debug for Atom<'_> {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        match self {
-            Self::Identifier(arg0) => f.debug_tuple("Identifier").field(arg0).finish(),
-            Self::Quote(arg0) => f.debug_tuple("Quote").field(arg0).finish(),
-            Self::Integer(arg0) => f.debug_tuple("Integer").field(arg0).finish(),
-            Self::Float(arg0) => f.debug_tuple("Float").field(arg0).finish(),
-            Self::List(arg0) => f.debug_list().entries(arg0.clone()).finish(),
+impl<'s> LispIter<'s> {
+    /// Returns an independent snapshot of the iterator at its current position, for
+    /// speculative parsing.
+    ///
+    /// Parse ahead on the fork; if that works out, make it permanent with
+    /// [`LispIter::commit`], otherwise just drop the fork and try something else from the
+    /// untouched original. A fork is a plain clone, so it's cheap.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new("(point 1 2)");
+    /// let mut list = iter.expect_list().unwrap();
+    /// let mut attempt = list.fork();
+    /// if attempt.expect_identifier() == Ok("point") {
+    ///     list.commit(attempt);
+    /// }
+    /// assert_eq!(list.expect_integer(), Ok(1));
+    /// ```
+    pub fn fork(&self) -> LispIter<'s> {
+        self.clone()
+    }
+
+    /// Moves `self` to where `fork`, made with [`LispIter::fork`], has got to.
+    pub fn commit(&mut self, fork: LispIter<'s>) {
+        debug_assert!(
+            core::ptr::eq(self.input, fork.input),
+            "committing a fork of another iterator"
+        );
+        *self = fork;
+    }
+
+    /// Consumes the next atom if `pred` returns `true` for it, returning whether it did.
+    ///
+    /// The atom is looked at through a clone of the iterator, so nothing is consumed when
+    /// `pred` returns `false` or the input is exhausted.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// let mut iter = LispIter::new("\"s\" 1 x");
+    /// assert!(iter.skip_if(|atom| matches!(atom, Atom::Quote(_))));
+    /// assert!(!iter.skip_if(|atom| matches!(atom, Atom::Identifier(_))));
+    /// assert!(iter.skip_if(|atom| matches!(atom, Atom::Integer(1))));
+    /// assert!(iter.skip_if(|_| true));
+    /// assert!(!iter.skip_if(|_| true));
+    /// ```
+    pub fn skip_if<F>(&mut self, pred: F) -> bool
+    where
+        F: FnOnce(&Atom<'s>) -> bool,
+    {
+        let mut fork = self.clone();
+        match fork.next() {
+            Some(atom) if pred(&atom) => {
+                *self = fork;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the next call to [`Iterator::next`] would return `None`.
+    ///
+    /// Doesn't consume anything: the check runs the lexer over a clone, skipping whitespace,
+    /// comments, stray `)`s and invalid byte strings without building the next atom.
+    ///
+    /// ```
+    /// let list = lisp_iter::LispIter::new("( ; just a comment\n)").next().unwrap();
+    /// assert!(list.as_list().unwrap().is_empty());
+    ///
+    /// let mut iter = lisp_iter::LispIter::new(") #| c |# #\"zz\" ] x");
+    /// assert!(!iter.is_empty());
+    /// assert!(iter.next().unwrap().eq_identifier("x"));
+    /// assert!(iter.is_empty());
+    /// assert!(lisp_iter::LispIter::new("").is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        let mut fork = self.clone();
+        while let Some((token, span)) = fork.lex() {
+            match token {
+                Token::LineComment | Token::BlockComment { .. } | Token::Close(_) => {}
+                Token::Bytes { closed } if !fork.valid_bytes(closed, &span) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Skips the next expression, returning whether there was one.
+    ///
+    /// The iterator ends up where [`Iterator::next`] would leave it, but nothing is parsed:
+    /// brackets are balanced and strings and comments stepped over, without building atoms
+    /// or converting numbers.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let source = "(a \")\" [b ; ]\n]) #| ( |# c";
+    /// let (mut skipped, mut parsed) = (LispIter::new(source), LispIter::new(source));
+    /// assert!(skipped.skip_expr());
+    /// parsed.next();
+    /// assert_eq!(skipped.byte_position(), parsed.byte_position());
+    /// assert!(skipped.next().unwrap().eq_identifier("c"));
+    /// assert!(!skipped.skip_expr());
+    /// ```
+    pub fn skip_expr(&mut self) -> bool {
+        self.next_extent().is_some()
+    }
+
+    /// Skips up to `n` expressions like [`LispIter::skip_expr`], returning how many there
+    /// were.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new("(a b) c \"d\" e");
+    /// assert_eq!(iter.skip_exprs(2), 2);
+    /// assert!(iter.next().unwrap().eq_quote("d"));
+    /// assert_eq!(iter.skip_exprs(5), 1);
+    /// assert_eq!(iter.skip_exprs(1), 0);
+    /// ```
+    pub fn skip_exprs(&mut self, n: usize) -> usize {
+        (0..n).take_while(|_| self.skip_expr()).count()
+    }
+
+    /// Returns the `n`th next atom, like [`Iterator::nth`], which forwards here.
+    ///
+    /// The `n` atoms before it are skipped structurally: brackets are balanced and strings
+    /// and comments stepped over, but no atoms are built and no numbers parsed. The result
+    /// is the same as calling [`Iterator::next`] `n + 1` times.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new(r#"(a ")" [b]) ; c
+    ///     #| d |# "e" f g"#);
+    /// assert!(iter.nth_expr(2).unwrap().eq_identifier("f"));
+    /// assert!(iter.nth_expr(0).unwrap().eq_identifier("g"));
+    /// assert!(iter.nth_expr(0).is_none());
+    /// assert!(LispIter::new("a b").nth(5).is_none());
+    /// ```
+    pub fn nth_expr(&mut self, n: usize) -> Option<Atom<'s>> {
+        for _ in 0..n {
+            self.next_extent()?;
+        }
+        self.next()
+    }
+
+    /// Consumes the next atom if it's the symbol `name`, returning whether it was.
+    ///
+    /// On a mismatch, or at the end of input, the iterator is left exactly where it was.
+    /// See [`LispIter::skip_if`].
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("define x 1");
+    /// assert!(!iter.next_is_symbol("lambda"));
+    /// assert!(iter.next_is_symbol("define"));
+    /// assert!(iter.next().unwrap().is_symbol("x"));
+    /// ```
+    pub fn next_is_symbol(&mut self, name: &str) -> bool {
+        self.skip_if(|atom| atom.is_symbol(name))
+    }
+
+    /// Consumes the next atom if it's an [`Atom::Identifier`] and returns its text,
+    /// leaving the iterator at the arguments that follow.
+    ///
+    /// Anything else, or the end of input, returns `None` and consumes nothing.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut args = LispIter::new("(move 3 -2)").next().unwrap().into_list_iter().unwrap();
+    /// match args.head_identifier() {
+    ///     Some("move") => {
+    ///         let (dx, dy) = (args.expect_integer().unwrap(), args.expect_integer().unwrap());
+    ///         assert_eq!((dx, dy), (3, -2));
+    ///     }
+    ///     Some(command) => panic!("unknown command {}", command),
+    ///     None => panic!("expected a command"),
+    /// }
+    /// ```
+    pub fn head_identifier(&mut self) -> Option<&'s str> {
+        let mut fork = self.clone();
+        let head = fork.next()?.as_identifier()?;
+        *self = fork;
+        Some(head)
+    }
+
+    /// Advances past the next [`Atom::Identifier`] equal to `name`, returning whether one was
+    /// found.
+    ///
+    /// On success the iterator is left just after the identifier; otherwise it's exhausted.
+    /// Only atoms at this level are looked at, not the contents of nested lists.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// let mut iter = LispIter::new("a (target) \"target\" target b");
+    /// assert!(iter.find_identifier("target"));
+    /// assert!(matches!(iter.next(), Some(Atom::Identifier("b"))));
+    /// assert!(!iter.find_identifier("target"));
+    /// assert!(iter.next().is_none());
+    /// ```
+    pub fn find_identifier(&mut self, name: &str) -> bool {
+        self.any(|atom| matches!(atom, Atom::Identifier(s) if s == name))
+    }
+
+    /// Follows `path` down nested lists, returning the atom after the head of the last one.
+    ///
+    /// Each segment picks the first list, among the remaining atoms or the elements of the
+    /// list picked before, whose head is the identifier equal to it. Doesn't consume
+    /// anything; returns `None` if a segment matches nothing, the final list has no second
+    /// element, or `path` is empty.
+    ///
+    /// ```
+    /// let doc = lisp_iter::LispIter::new(r#"(server (net (port 8080) (host "0.0.0.0")))"#);
+    /// assert!(doc.get(&["server", "net", "port"]).unwrap().eq_integer(8080));
+    /// assert!(doc.get(&["server", "net", "host"]).unwrap().eq_quote("0.0.0.0"));
+    /// assert!(doc.get(&["server", "disk"]).is_none());
+    /// ```
+    pub fn get(&self, path: &[&str]) -> Option<Atom<'s>> {
+        self.get_all(path).next()
+    }
+
+    /// Like [`LispIter::get`], but yields every match in source order, trying each list a
+    /// segment matches rather than only the first.
+    ///
+    /// Matches are found by searching again from the top, past the previous match, so this
+    /// is meant for the handful of repeats a config file has rather than large inputs.
+    ///
+    /// ```
+    /// let doc = lisp_iter::LispIter::new("(site (port 80)) (site (port 443) (port 8443))");
+    /// let ports: Vec<_> = doc.get_all(&["site", "port"]).filter_map(|p| p.as_integer()).collect();
+    /// assert_eq!(ports, [80, 443, 8443]);
+    /// ```
+    pub fn get_all<'p>(&self, path: &'p [&'p str]) -> PathIter<'s, 'p> {
+        PathIter {
+            root: self.clone(),
+            path,
+            from: 0,
+        }
+    }
+
+    /// Wraps the remaining atoms into a single [`Atom::List`].
+    ///
+    /// A list atom is nothing but a [`LispIter`] over its contents, so this is just
+    /// `Atom::List(self)`: iterating the list picks up where `self` left off.
+    ///
+    /// To read a whole input as an implicit form with a head, like `(begin ...)`, see
+    /// [`LispIter::new_prefixed`].
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// let body = LispIter::new("(define x 1) (print x)").collect_to_list_atom();
+    /// let mut forms = body.into_list_iter().unwrap();
+    /// assert_eq!(forms.next_spanned().unwrap().1.outer, 0..12);
+    /// assert!(matches!(forms.next(), Some(Atom::List(_))));
+    /// ```
+    pub fn collect_to_list_atom(self) -> Atom<'s> {
+        Atom::List(self)
+    }
+
+    /// Returns an iterator yielding exactly one [`Atom::List`] holding the remaining atoms.
+    ///
+    /// Useful where an atom stream is expected but the remaining atoms should be treated
+    /// as one form. See [`LispIter::collect_to_list_atom`]. It's the remaining input read
+    /// with [`LispIter::new_prefixed`] between `(` and `)`, so it keeps the configuration,
+    /// and the list's span covers just the remaining input. Whatever is left of a prefix or
+    /// suffix from [`LispIter::new_prefixed`] itself is dropped.
+    ///
+    /// Only the atoms not yet consumed are wrapped, and an exhausted iterator still
+    /// yields one (empty) list:
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new("skipped a b");
+    /// iter.next();
+    /// let mut wrapped = iter.clone().wrap_remaining_as_list();
+    /// let (list, span) = wrapped.next_spanned().unwrap();
+    /// assert_eq!(span.outer, 7..11);
+    /// assert_eq!(list.as_list().unwrap().count(), 2);
+    /// assert!(wrapped.next().is_none());
+    ///
+    /// iter.by_ref().for_each(drop);
+    /// let mut wrapped = iter.wrap_remaining_as_list();
+    /// assert!(wrapped.next().unwrap().as_list().unwrap().next().is_none());
+    /// assert!(wrapped.next().is_none());
+    /// ```
+    pub fn wrap_remaining_as_list(self) -> LispIter<'s> {
+        let rest = LispIter::with_config(self.remainder(), self.config);
+        LispIter {
+            base: self.byte_position(),
+            ..rest
+        }
+        .affixed("(", ")")
+    }
+
+    /// Turns the iterator into one over every atom below it, paired with its depth.
+    ///
+    /// Atoms are yielded in source order, each list before its contents. The iterator's
+    /// own atoms are at depth 0, and the contents of a list at depth `d` are at `d + 1`.
+    /// Nesting is tracked with a counter, so deep input needs no extra memory.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let depths: Vec<_> = LispIter::new("a (b [c]) d")
+    ///     .zip_with_depth()
+    ///     .map(|(depth, atom)| (depth, atom.as_identifier()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     depths,
+    ///     [(0, Some("a")), (0, None), (1, Some("b")), (1, None), (2, Some("c")), (0, Some("d"))]
+    /// );
+    ///
+    /// let deep = "(".repeat(2_000) + &")".repeat(2_000);
+    /// assert_eq!(LispIter::new(&deep).zip_with_depth().last().unwrap().0, 1_999);
+    /// ```
+    pub fn zip_with_depth(self) -> DepthIter<'s> {
+        DepthIter {
+            walker: Walker::new(self),
+        }
+    }
+
+    /// Splits the remaining atoms into segments separated by the identifier `name`.
+    ///
+    /// Each segment is a [`LispIter`] over the source between two separators, so its atoms
+    /// are parsed again as it's iterated and keep their absolute spans. Like
+    /// [`str::split`], separators at the ends or next to each other produce empty segments.
+    ///
+    /// Only top-level identifiers split; a nested or quoted `name` stays in its segment:
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let lens = |src| -> Vec<usize> {
+    ///     LispIter::new(src).split_at_identifier("|").map(|seg| seg.count()).collect()
+    /// };
+    /// assert_eq!(lens("a | b c | | d"), [1, 2, 0, 1]);
+    /// assert_eq!(lens("| a |"), [0, 1, 0]);
+    /// assert_eq!(lens(r#"(| x) "|" | y"#), [2, 1]);
+    ///
+    /// let mut segments = LispIter::new("a | b").split_at_identifier("|");
+    /// segments.next();
+    /// assert_eq!(segments.next().unwrap().next_spanned().unwrap().1.outer, 4..5);
+    /// ```
+    pub fn split_at_identifier<'n>(self, name: &'n str) -> SplitIter<'s, 'n> {
+        SplitIter {
+            iter: Some(self),
+            name,
+        }
+    }
+
+    /// Yields atoms up to, but not including, the identifier `name`.
+    ///
+    /// The identifier is left in the stream, so the next call to [`Iterator::next`] on
+    /// `self` returns it. If it never shows up, the remaining atoms are all yielded and
+    /// `self` ends up exhausted.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new("a (else) b else c");
+    /// assert_eq!(iter.take_until_identifier("else").count(), 3);
+    /// assert!(iter.next().unwrap().eq_identifier("else"));
+    /// assert!(iter.next().unwrap().eq_identifier("c"));
+    ///
+    /// let mut iter = LispIter::new("a b");
+    /// assert_eq!(iter.take_until_identifier("else").count(), 2);
+    /// assert!(iter.next().is_none());
+    /// ```
+    pub fn take_until_identifier<'i, 'n>(&'i mut self, name: &'n str) -> TakeUntilIter<'i, 's, 'n> {
+        TakeUntilIter { iter: self, name }
+    }
+
+    /// Turns the iterator into one over the `(key value)` pairs of a property list, such
+    /// as the contents of `(:host "example.com" :port 80 :tls)`.
+    ///
+    /// Each key is a `:keyword`, i.e. an [`Atom::Quote`] written with a `:`. It's paired
+    /// with the atom after it, or with `None` if it's directly followed by another keyword
+    /// or the end of input, like `:tls` above. Any other atom in key position is reported
+    /// as [`ParseError::ExpectedKeyword`] and skipped. Duplicate keys are yielded as they
+    /// come.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// let mut plist = LispIter::new(r#":host "example.com" :port 80 :tls"#).plist();
+    /// let (key, value) = plist.next().unwrap().unwrap();
+    /// assert!(key.eq_quote("host") && value.unwrap().eq_quote("example.com"));
+    /// let (key, value) = plist.next().unwrap().unwrap();
+    /// assert!(key.eq_quote("port") && value.unwrap().eq_integer(80));
+    /// let (key, value) = plist.next().unwrap().unwrap();
+    /// assert!(key.eq_quote("tls") && value.is_none());
+    /// assert!(plist.next().is_none());
+    /// ```
+    pub fn plist(self) -> PlistIter<'s> {
+        PlistIter { iter: self }
+    }
+
+    /// Whether an atom parsed from `range` of the lexed text is a `:keyword` rather than a
+    /// string.
+    fn is_keyword(&self, range: &Range<usize>) -> bool {
+        self.text(range.clone()).starts_with(':')
+    }
+
+    /// Moves the remaining atoms into `buf`, returning the filled part of it.
+    ///
+    /// If there are more atoms than fit, the buffer is filled and [`Overflow`] is returned
+    /// with the iterator just after the last atom written, so the caller can decide whether
+    /// to go on. Slots past the returned part are left as they were.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, Overflow};
+    ///
+    /// let mut buf: [Option<_>; 4] = Default::default();
+    /// let args = LispIter::new("1 2 3").collect_into(&mut buf).unwrap();
+    /// assert_eq!(args.len(), 3);
+    ///
+    /// let mut iter = LispIter::new("1 2 3 4 5");
+    /// assert_eq!(iter.collect_into(&mut buf), Err(Overflow { written: 4 }));
+    /// assert!(iter.next().unwrap().eq_integer(5));
+    /// ```
+    pub fn collect_into<'b>(
+        &mut self,
+        buf: &'b mut [Option<Atom<'s>>],
+    ) -> Result<&'b [Option<Atom<'s>>], Overflow> {
+        let mut written = 0;
+        for slot in buf.iter_mut() {
+            match self.next() {
+                Some(atom) => *slot = Some(atom),
+                None => break,
+            }
+            written += 1;
+        }
+        if written == buf.len() && !self.is_empty() {
+            return Err(Overflow { written });
+        }
+        Ok(&buf[..written])
+    }
+
+    /// Consumes the iterator, moving up to `N` atoms into an array and returning it with
+    /// the number of atoms written. Slots past that are `None`.
+    ///
+    /// For inputs whose size is bounded statically; atoms beyond the first `N` are never
+    /// parsed. See [`LispIter::collect_into`] to keep the iterator and detect overflow.
+    ///
+    /// ```
+    /// let (atoms, count) = lisp_iter::LispIter::new("a b").drain_into_array::<4>();
+    /// assert_eq!(count, 2);
+    /// assert!(atoms[1].as_ref().unwrap().eq_identifier("b") && atoms[2].is_none());
+    /// ```
+    pub fn drain_into_array<const N: usize>(mut self) -> ([Option<Atom<'s>>; N], usize) {
+        let mut count = 0;
+        let atoms = core::array::from_fn(|_| {
+            let atom = self.next();
+            count += atom.is_some() as usize;
+            atom
+        });
+        (atoms, count)
+    }
+
+    /// Turns the iterator into one over the text of its [`Atom::Identifier`]s, skipping
+    /// every other atom.
+    ///
+    /// Like the other filtering adapters, this only looks at atoms at this level, not into
+    /// nested lists. They chain to reach one level further, or see [`flatten`] for every
+    /// leaf of a document:
+    ///
+    /// ```
+    /// let doc = r#"(title "Intro") (p "Hello" (em "world")) 42"#;
+    /// let mut strings = lisp_iter::LispIter::new(doc).lists().flat_map(|list| list.quotes());
+    /// assert_eq!(strings.next(), Some("Intro"));
+    /// assert_eq!(strings.next(), Some("Hello"));
+    /// ```
+    pub fn identifiers(self) -> Filtered<'s, &'s str> {
+        self.filtered(|atom| atom.as_identifier())
+    }
+
+    /// Turns the iterator into one over the contents of its [`Atom::Quote`]s. See
+    /// [`LispIter::identifiers`].
+    pub fn quotes(self) -> Filtered<'s, &'s str> {
+        self.filtered(|atom| atom.as_quote())
+    }
+
+    /// Turns the iterator into one over the values of its [`Atom::Integer`]s. See
+    /// [`LispIter::identifiers`].
+    pub fn integers(self) -> Filtered<'s, i64> {
+        self.filtered(|atom| atom.as_integer())
+    }
+
+    /// Turns the iterator into one over the values of its [`Atom::Float`]s. See
+    /// [`LispIter::identifiers`].
+    pub fn floats(self) -> Filtered<'s, f64> {
+        self.filtered(|atom| atom.as_float())
+    }
+
+    /// Turns the iterator into one over the iterators of its [`Atom::List`]s. See
+    /// [`LispIter::identifiers`].
+    pub fn lists(self) -> Filtered<'s, LispIter<'s>> {
+        self.filtered(Atom::into_list_iter)
+    }
+
+    fn filtered<T>(self, select: fn(Atom<'s>) -> Option<T>) -> Filtered<'s, T> {
+        Filtered { iter: self, select }
+    }
+
+    /// Turns the iterator into one that pairs each atom with metadata computed by `f`, such
+    /// as a file name or an expansion depth.
+    ///
+    /// `f` is called with the atom and the byte offset it starts at, relative to the
+    /// outermost input as with spans.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("a (b)").annotate(|_, offset| ("main.lisp", offset));
+    /// assert_eq!(iter.next().unwrap().1, ("main.lisp", 0));
+    /// assert_eq!(iter.next().unwrap().1, ("main.lisp", 2));
+    /// ```
+    pub fn annotate<M, F>(self, f: F) -> AnnotatedIter<'s, F>
+    where
+        F: FnMut(&Atom<'s>, usize) -> M,
+    {
+        AnnotatedIter { iter: self, f }
+    }
+
+    /// Turns the iterator into one over [`SymbolAtom`]s, whose identifiers and keywords are
+    /// mapped by `f`, such as to intern them while the text is at hand.
+    ///
+    /// Lists, vectors and maps come with iterators that map their contents the same way,
+    /// each holding a clone of `f`. To share state like an interner between them, have `f`
+    /// capture a shared reference, as to a `RefCell`.
+    ///
+    /// ```
+    /// use core::cell::RefCell;
+    /// use lisp_iter::{LispIter, SymbolAtom};
+    ///
+    /// let names = RefCell::new(Vec::new());
+    /// let intern = |name: &str| {
+    ///     let mut names = names.borrow_mut();
+    ///     let id = names.iter().position(|n: &String| n == name).unwrap_or_else(|| {
+    ///         names.push(name.to_string());
+    ///         names.len() - 1
+    ///     });
+    ///     id as u32
+    /// };
+    /// let mut iter = LispIter::new("(f x :key (g x))").map_symbols(intern);
+    /// let Some(SymbolAtom::List(mut list)) = iter.next() else { panic!() };
+    /// assert!(matches!(list.next(), Some(SymbolAtom::Identifier(0))));
+    /// assert!(matches!(list.next(), Some(SymbolAtom::Identifier(1))));
+    /// assert!(matches!(list.next(), Some(SymbolAtom::Keyword(2))));
+    /// let Some(SymbolAtom::List(mut inner)) = list.next() else { panic!() };
+    /// assert!(matches!(inner.next(), Some(SymbolAtom::Identifier(3))));
+    /// assert!(matches!(inner.next(), Some(SymbolAtom::Identifier(1))));
+    /// ```
+    pub fn map_symbols<S, F>(self, f: F) -> SymbolIter<'s, F>
+    where
+        F: FnMut(&'s str) -> S + Clone,
+    {
+        SymbolIter { iter: self, f }
+    }
+
+    /// Turns the iterator into one over every run of `N` consecutive atoms, like
+    /// `slice::windows`, without allocating.
+    ///
+    /// Rather than buffering atoms, each window is parsed afresh from a clone of the
+    /// iterator, so every atom is parsed up to `N` times. Fewer than `N` atoms give no
+    /// windows at all. See `LispIter::windows` with the `alloc` feature for a size chosen
+    /// at run time.
+    ///
+    /// Panics if `N` is 0.
+    ///
+    /// ```
+    /// let iter = lisp_iter::LispIter::new("define x 1 define y 2").windows_const::<3>();
+    /// let defines = iter.filter(|[head, ..]| head.eq_identifier("define")).count();
+    /// assert_eq!(defines, 2);
+    /// ```
+    pub fn windows_const<const N: usize>(self) -> WindowsConst<'s, N> {
+        assert!(N > 0, "window size must be non-zero");
+        WindowsConst { iter: self }
+    }
+
+    /// Takes atoms from `self` and `other` in alternation, starting with `self`. Once one of
+    /// them is exhausted, the rest of the other follows.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let pairs = LispIter::new("width height").interleave(LispIter::new("800 600"));
+    /// let text: Vec<_> = pairs.map(|atom| format!("{:?}", atom)).collect();
+    /// assert_eq!(text, ["Identifier(\"width\")", "Integer(800)", "Identifier(\"height\")", "Integer(600)"]);
+    /// ```
+    pub fn interleave(self, other: LispIter<'s>) -> InterleavedIter<'s> {
+        InterleavedIter {
+            first: self,
+            second: other,
+            second_next: false,
+        }
+    }
+
+    /// Continues with the atoms of `second` once `self` is exhausted, e.g. to run user code
+    /// after a prelude.
+    ///
+    /// The two inputs stay separate: a list left unclosed at the end of `self` ends there
+    /// rather than running on into `second`, whose spans are relative to `second` itself.
+    /// It's parsed with the same [`ParseConfig`] as `self`.
+    ///
+    /// ```
+    /// let prelude = "(define nil ())";
+    /// let mut iter = lisp_iter::LispIter::new(prelude).chain_input("(print nil)");
+    /// assert_eq!(iter.by_ref().count(), 2);
+    /// assert!(iter.in_second());
+    /// ```
+    pub fn chain_input(self, second: &'s str) -> ChainedLispIter<'s> {
+        ChainedLispIter {
+            second: LispIter::with_config(second, self.config),
+            first: self,
+            in_second: false,
+        }
+    }
+
+    /// Borrows the iterator as an adapter, so that adapters taking `self` by value, like
+    /// `take`, leave it usable afterwards. Same as [`Iterator::by_ref`], as a named type.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new("a b c d");
+    /// let head: Vec<_> = iter.by_ref_adapter().take(2).collect();
+    /// assert_eq!(head.len(), 2);
+    /// assert_eq!(iter.next().unwrap().as_identifier(), Some("c"));
+    /// assert_eq!(iter.by_ref_adapter().count(), 1);
+    /// assert!(iter.next().is_none());
+    /// ```
+    pub fn by_ref_adapter(&mut self) -> LispIterByRef<'_, 's> {
+        LispIterByRef { iter: self }
+    }
+
+    /// Parses the next atom, failing with [`ParseError::UnexpectedEnd`] at the end of input
+    /// instead of returning `None`.
+    ///
+    /// Pairs with the `expect_*` helpers on [`Atom`], whose [`TypeMismatch`] converts into
+    /// a [`ParseError`], so `iter.expect_next()?.expect_integer()?` checks both at once.
+    ///
+    /// ```
+    /// use lisp_iter::{AtomKind, LispIter, ParseError};
+    ///
+    /// fn width<'a>(iter: &mut LispIter<'a>) -> Result<i64, ParseError<'a>> {
+    ///     Ok(iter.expect_next()?.expect_integer()?)
+    /// }
+    ///
+    /// let mut iter = LispIter::new("80 wide");
+    /// assert_eq!(width(&mut iter), Ok(80));
+    /// let mismatch = ParseError::UnexpectedAtom {
+    ///     expected: AtomKind::Integer,
+    ///     got: AtomKind::Identifier,
+    /// };
+    /// assert_eq!(width(&mut iter), Err(mismatch));
+    /// assert_eq!(width(&mut iter), Err(ParseError::UnexpectedEnd { expected: None }));
+    /// ```
+    pub fn expect_next(&mut self) -> Result<Atom<'s>, ParseError<'s>> {
+        self.try_next()
+            .ok_or(ParseError::UnexpectedEnd { expected: None })?
+    }
+
+    /// Parses the next atom, requiring it to be of the given kind.
+    fn expect(&mut self, expected: AtomKind) -> Result<Atom<'s>, ParseError<'s>> {
+        let atom = self.try_next().ok_or(ParseError::UnexpectedEnd {
+            expected: Some(expected),
+        })??;
+        match atom.kind() {
+            got if got == expected => Ok(atom),
+            got => Err(ParseError::UnexpectedAtom { expected, got }),
+        }
+    }
+
+    /// Parses the next atom as an [`Atom::Identifier`].
+    ///
+    /// Fails with [`ParseError::UnexpectedAtom`] on any other atom, with
+    /// [`ParseError::UnexpectedEnd`] at the end of input, or with whatever
+    /// [`LispIter::try_next`] reports.
+    ///
+    /// ```
+    /// use lisp_iter::{AtomKind, LispIter, ParseError};
+    ///
+    /// let mut iter = LispIter::new("(move 3 0.5 \"fast\") stop");
+    /// let mut args = iter.expect_list().unwrap();
+    /// assert_eq!(args.expect_identifier(), Ok("move"));
+    /// assert_eq!(args.expect_integer(), Ok(3));
+    /// assert_eq!(args.expect_float(), Ok(0.5));
+    /// assert_eq!(args.expect_quote(), Ok("fast"));
+    /// let end = ParseError::UnexpectedEnd { expected: Some(AtomKind::Integer) };
+    /// assert_eq!(args.expect_integer(), Err(end));
+    /// let mismatch = ParseError::UnexpectedAtom {
+    ///     expected: AtomKind::List,
+    ///     got: AtomKind::Identifier,
+    /// };
+    /// assert_eq!(iter.expect_list().err(), Some(mismatch));
+    /// ```
+    pub fn expect_identifier(&mut self) -> Result<&'s str, ParseError<'s>> {
+        match self.expect(AtomKind::Identifier)? {
+            Atom::Identifier(s) => Ok(s),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parses the next atom as an [`Atom::Quote`]. See [`LispIter::expect_identifier`].
+    pub fn expect_quote(&mut self) -> Result<&'s str, ParseError<'s>> {
+        match self.expect(AtomKind::Quote)? {
+            Atom::Quote(s) => Ok(s),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parses the next atom as an [`Atom::Integer`]. See [`LispIter::expect_identifier`].
+    pub fn expect_integer(&mut self) -> Result<i64, ParseError<'s>> {
+        match self.expect(AtomKind::Integer)? {
+            Atom::Integer(v) => Ok(v),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parses the next atom as an [`Atom::Float`]. See [`LispIter::expect_identifier`].
+    pub fn expect_float(&mut self) -> Result<f64, ParseError<'s>> {
+        match self.expect(AtomKind::Float)? {
+            Atom::Float(v) => Ok(v),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parses the next atom as an [`Atom::List`], returning an iterator over its contents.
+    /// See [`LispIter::expect_identifier`].
+    pub fn expect_list(&mut self) -> Result<LispIter<'s>, ParseError<'s>> {
+        match self.expect(AtomKind::List)? {
+            Atom::List(iter) => Ok(iter),
+            _ => unreachable!(),
         }
     }
 }
 
-impl<'s> Iterator for LispIter<'s> {
+/// Iterator borrowing a [`LispIter`], see [`LispIter::by_ref_adapter`].
+pub struct LispIterByRef<'i, 's> {
+    iter: &'i mut LispIter<'s>,
+}
+
+impl<'s> Iterator for LispIterByRef<'_, 's> {
     type Item = Atom<'s>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (start, c) = self.chars.by_ref().find(|(_, c)| !c.is_whitespace())?;
-        match c {
-            ';' => {
-                self.chars.find(|(_, c)| *c == '\n');
-                self.next()
-            }
-            '(' => {
-                let mut popen = 0;
-                let mut quoted = false;
-                let mut commented = false;
-                let (end, _) = self
-                    .chars
-                    .by_ref()
-                    .find(|(_, c)| {
-                        if popen == 0 && !quoted && !commented && *c == ')' {
-                            return true;
-                        } else {
-                            match *c {
-                                ';' => commented = true,
-                                '\n' => commented = false,
-                                '"' if !commented => quoted = !quoted,
-                                '(' if !quoted && !commented => popen += 1,
-                                ')' if !quoted && !commented => popen -= 1,
-                                _ => {}
-                            }
-                            return false;
-                        }
-                    })
-                    .unwrap_or_else(|| (self.input.len(), '\0')); // unclosed list
-
-                Some(Atom::List(LispIter::new(
-                    &self.input[start + '('.len_utf8()..end],
-                )))
-            }
-            ')' => {
-                unreachable!()
-            }
-            '"' => {
-                let (end, _) = self
-                    .chars
-                    .by_ref()
-                    .find(|(_, c)| *c == '"')
-                    .unwrap_or_else(|| (self.input.len(), '\0')); // unclosed quote
-
-                Some(Atom::Quote(&self.input[start + '"'.len_utf8()..end]))
-            }
-            ':' => {
-                let (end, _) = self
-                    .chars
-                    .by_ref()
-                    .find(|(_, c)| c.is_whitespace())
-                    .unwrap();
-
-                Some(Atom::Quote(&self.input[start + ':'.len_utf8()..end]))
-            }
-            '-' | '0'..='9' => {
-                let (end, _) = self
-                    .chars
-                    .by_ref()
-                    .find(|(_, c)| c.is_whitespace())
-                    .unwrap();
-
-                if let Ok(v) = self.input[start..end].parse() {
-                    Some(Atom::Integer(v))
-                } else if let Ok(v) = self.input[start..end].parse() {
-                    Some(Atom::Float(v))
-                } else {
-                    Some(Atom::Identifier(&self.input[start..end])) // fallback
-                }
-            }
-            _ => {
-                let (end, _) = self
-                    .chars
-                    .by_ref()
-                    .find(|(_, c)| c.is_whitespace())
-                    .unwrap();
-                Some(Atom::Identifier(&self.input[start..end]))
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Iterator over every atom in a tree with its depth, see [`LispIter::zip_with_depth`].
+#[derive(Clone)]
+pub struct DepthIter<'s> {
+    walker: Walker<'s>,
+}
+
+impl<'s> Iterator for DepthIter<'s> {
+    type Item = (usize, Atom<'s>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let depth = self.walker.depth;
+            match self.walker.next_event()? {
+                Event::Enter(atom) | Event::Leaf(atom) => return Some((depth, atom)),
+                Event::Exit => continue,
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Calls `f` on `atom` and every atom below it, depth-first in source order, until `f`
+/// returns `false`.
+///
+/// A list is passed to `f` before its contents. Returning `false` stops the whole walk
+/// right away, not just the descent into the current list. Like
+/// [`LispIter::zip_with_depth`], the walk keeps no stack, so deep nesting is fine.
+///
+/// ```
+/// let atom = lisp_iter::LispIter::new("(a (b (c (d))) e)").next().unwrap();
+/// let mut seen = 0;
+/// lisp_iter::walk(atom, &mut |atom| {
+///     seen += 1;
+///     !atom.eq_identifier("c")
+/// });
+/// assert_eq!(seen, 6); // (a ...), a, (b ...), b, (c ...), c
+///
+/// let deep = "[".repeat(2_000) + "x" + &"]".repeat(2_000);
+/// let atom = lisp_iter::LispIter::new(&deep).next().unwrap();
+/// let mut seen = 0;
+/// lisp_iter::walk(atom.clone(), &mut |atom| {
+///     seen += 1;
+///     !atom.eq_identifier("x")
+/// });
+/// assert_eq!(seen, 2_001);
+/// let mut seen = 0;
+/// lisp_iter::walk(atom, &mut |_| {
+///     seen += 1;
+///     seen < 1_000
+/// });
+/// assert_eq!(seen, 1_000);
+/// ```
+pub fn walk<F>(atom: Atom<'_>, f: &mut F)
+where
+    F: FnMut(&Atom<'_>) -> bool,
+{
+    if !f(&atom) {
+        return;
+    }
+    let (Atom::List(iter) | Atom::Vector(iter) | Atom::Map(iter)) = atom else {
+        return;
+    };
+    let mut walker = Walker::new(iter);
+    while let Some(event) = walker.next_event() {
+        match event {
+            Event::Enter(atom) | Event::Leaf(atom) if !f(&atom) => return,
+            _ => {}
+        }
+    }
+}
+
+/// Event passed to the visitor of [`Atom::walk`].
+#[derive(Clone, Debug)]
+pub enum WalkEvent<'a> {
+    /// A list, vector or map was entered. Its contents follow, then a matching `Exit`.
+    Enter(Atom<'a>),
+    /// Any other atom.
+    Leaf(Atom<'a>),
+    /// The list, vector or map of the last unmatched `Enter` has ended.
+    Exit,
+}
+
+/// What [`Atom::walk`] should do after a visitor call.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WalkControl {
+    Continue,
+    /// Don't descend into the container just entered. Its `Exit` is still delivered.
+    /// Same as `Continue` for other events.
+    SkipChildren,
+    /// End the walk right away.
+    Stop,
+}
+
+impl<'a> Atom<'a> {
+    /// Walks this atom and everything below it in source order, calling `visitor` with
+    /// each [`WalkEvent`] and its depth.
+    ///
+    /// The depth is the number of unmatched `Enter`s before the event, so it's 0 for this
+    /// atom, and an `Exit` has the same depth as its `Enter`. The walk keeps no stack, so
+    /// deep nesting is fine. See [`walk`] for a plain closure over atoms.
+    ///
+    /// ```
+    /// use core::fmt::Write;
+    /// use lisp_iter::{LispIter, WalkControl, WalkEvent};
+    ///
+    /// let atom = LispIter::new("(html (head (title x)) (body (p a) (p b)))").next().unwrap();
+    /// let mut outline = String::new();
+    /// atom.walk(|event, depth| {
+    ///     match event {
+    ///         WalkEvent::Enter(list) => {
+    ///             let tag = list.as_list().and_then(|mut l| l.next()).unwrap();
+    ///             writeln!(outline, "{:width$}{:?}", "", tag, width = depth * 2).unwrap();
+    ///             if tag.eq_identifier("head") {
+    ///                 return WalkControl::SkipChildren;
+    ///             }
+    ///         }
+    ///         WalkEvent::Leaf(_) | WalkEvent::Exit => {}
+    ///     }
+    ///     WalkControl::Continue
+    /// });
+    /// assert_eq!(
+    ///     outline,
+    ///     "Identifier(\"html\")\n  Identifier(\"head\")\n  Identifier(\"body\")\n    \
+    ///      Identifier(\"p\")\n    Identifier(\"p\")\n",
+    /// );
+    /// ```
+    pub fn walk<F>(&self, mut visitor: F)
+    where
+        F: FnMut(WalkEvent<'a>, usize) -> WalkControl,
+    {
+        let (Atom::List(iter) | Atom::Vector(iter) | Atom::Map(iter)) = self else {
+            visitor(WalkEvent::Leaf(self.clone()), 0);
+            return;
+        };
+        match visitor(WalkEvent::Enter(self.clone()), 0) {
+            WalkControl::Continue => {}
+            WalkControl::SkipChildren => {
+                visitor(WalkEvent::Exit, 0);
+                return;
+            }
+            WalkControl::Stop => return,
+        }
+        let mut walker = Walker::new(iter.clone());
+        loop {
+            let depth = walker.depth + 1;
+            let control = match walker.next_event() {
+                Some(Event::Enter(atom)) => match visitor(WalkEvent::Enter(atom), depth) {
+                    WalkControl::SkipChildren => {
+                        walker.iter.close_list();
+                        walker.depth -= 1;
+                        visitor(WalkEvent::Exit, depth)
+                    }
+                    control => control,
+                },
+                Some(Event::Leaf(atom)) => visitor(WalkEvent::Leaf(atom), depth),
+                Some(Event::Exit) => visitor(WalkEvent::Exit, walker.depth + 1),
+                None => {
+                    visitor(WalkEvent::Exit, 0);
+                    return;
+                }
+            };
+            if control == WalkControl::Stop {
+                return;
+            }
+        }
+    }
+}
+
+/// Yields every atom in `input` that isn't a list, vector or map, at any depth, in source
+/// order.
+///
+/// Containers are descended into in place, as by [`LispIter::zip_with_depth`], so no stack
+/// is kept and deeply nested input is fine. Empty containers contribute nothing.
+///
+/// ```
+/// let leaves: Vec<_> = lisp_iter::flatten("(a (b () [c]) 1)").collect();
+/// assert_eq!(leaves.len(), 4);
+/// assert!(leaves[2].eq_identifier("c") && leaves[3].eq_integer(1));
+///
+/// let deep = "(a ".repeat(2_000) + "b" + &")".repeat(2_000);
+/// let mut leaves = lisp_iter::flatten(&deep);
+/// assert!(leaves.by_ref().take(2_000).all(|leaf| leaf.eq_identifier("a")));
+/// assert!(leaves.next().unwrap().eq_identifier("b"));
+/// assert!(leaves.next().is_none());
+/// ```
+pub fn flatten(input: &str) -> impl Iterator<Item = Atom<'_>> + '_ {
+    let mut walker = Walker::new(LispIter::new(input));
+    core::iter::from_fn(move || loop {
+        match walker.next_event()? {
+            Event::Leaf(atom) => return Some(atom),
+            Event::Enter(_) | Event::Exit => continue,
+        }
+    })
+}
+
+/// Iterator over the segments between separators, see [`LispIter::split_at_identifier`].
+#[derive(Clone)]
+pub struct SplitIter<'s, 'n> {
+    iter: Option<LispIter<'s>>,
+    name: &'n str,
+}
+
+impl<'s> Iterator for SplitIter<'s, '_> {
+    type Item = LispIter<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let iter = self.iter.as_mut()?;
+        let start = iter.offset();
+        loop {
+            match iter.next_ranged() {
+                Some((atom, _, range)) if atom.eq_identifier(self.name) => {
+                    return Some(iter.sublist(start..range.start));
+                }
+                Some(_) => continue,
+                None => {
+                    let segment = iter.sublist(start..iter.end());
+                    self.iter = None;
+                    return Some(segment);
+                }
+            }
+        }
+    }
+}
+
+/// Iterator over the atoms of one kind, see [`LispIter::identifiers`] and its siblings.
+#[derive(Clone)]
+pub struct Filtered<'s, T> {
+    iter: LispIter<'s>,
+    select: fn(Atom<'s>) -> Option<T>,
+}
+
+impl<T> Iterator for Filtered<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.by_ref().find_map(self.select)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Iterator over atoms paired with metadata, see [`LispIter::annotate`].
+#[derive(Clone)]
+pub struct AnnotatedIter<'s, F> {
+    iter: LispIter<'s>,
+    f: F,
+}
+
+impl<'s, M, F> Iterator for AnnotatedIter<'s, F>
+where
+    F: FnMut(&Atom<'s>, usize) -> M,
+{
+    type Item = (Atom<'s>, M);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (atom, span) = self.iter.next_spanned()?;
+        let meta = (self.f)(&atom, span.outer.start);
+        Some((atom, meta))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Iterator over atoms with mapped identifiers and keywords, see
+/// [`LispIter::map_symbols`].
+#[derive(Clone)]
+pub struct SymbolIter<'s, F> {
+    iter: LispIter<'s>,
+    f: F,
+}
+
+impl<'s, S, F> Iterator for SymbolIter<'s, F>
+where
+    F: FnMut(&'s str) -> S + Clone,
+{
+    type Item = SymbolAtom<'s, S, F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (atom, _, range) = self.iter.next_ranged()?;
+        let nested = |iter| SymbolIter {
+            iter,
+            f: self.f.clone(),
+        };
+        Some(match atom {
+            Atom::Identifier(s) => SymbolAtom::Identifier((self.f)(s)),
+            Atom::Quote(s) if self.iter.is_keyword(&range) => SymbolAtom::Keyword((self.f)(s)),
+            Atom::Quote(s) => SymbolAtom::Quote(s),
+            Atom::Integer(v) => SymbolAtom::Integer(v),
+            Atom::Float(v) => SymbolAtom::Float(v),
+            Atom::Float32(v) => SymbolAtom::Float32(v),
+            Atom::Rational(n, d) => SymbolAtom::Rational(n, d),
+            Atom::Bytes(hex) => SymbolAtom::Bytes(hex),
+            Atom::List(iter) => SymbolAtom::List(nested(iter)),
+            Atom::Vector(iter) => SymbolAtom::Vector(nested(iter)),
+            Atom::Map(iter) => SymbolAtom::Map(nested(iter)),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// An [`Atom`] whose identifiers and keywords were mapped to `S`, see
+/// [`LispIter::map_symbols`].
+///
+/// Keywords get a variant of their own, without the `:`, rather than being quotes.
+#[derive(Clone)]
+pub enum SymbolAtom<'s, S, F> {
+    Identifier(S),
+    Keyword(S),
+    Quote(&'s str),
+    Integer(i64),
+    Float(f64),
+    Float32(f32),
+    Rational(i64, u64),
+    Bytes(&'s [u8]),
+    List(SymbolIter<'s, F>),
+    Vector(SymbolIter<'s, F>),
+    Map(SymbolIter<'s, F>),
+}
+
+/// Comments belonging to an atom, see [`LispIter::next_with_comments`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comments<'s> {
+    /// The leading comments verbatim, from the start of the first to the end of the last,
+    /// or `""` if there are none.
+    pub leading: &'s str,
+    /// A comment following the atom on the same line, verbatim.
+    pub trailing: Option<&'s str>,
+    config: ParseConfig,
+}
+
+impl<'s> Comments<'s> {
+    /// Each of the leading comments verbatim, markers included. Line comments come
+    /// without their newline.
+    pub fn leading_comments(&self) -> impl Iterator<Item = &'s str> {
+        let mut lexer = LispIter::with_config(self.leading, self.config);
+        core::iter::from_fn(move || {
+            let (_, range) = lexer.lex()?;
+            Some(lexer.text(range))
+        })
+    }
+}
+
+/// Iterator over runs of consecutive atoms, see [`LispIter::windows_const`].
+#[derive(Clone)]
+pub struct WindowsConst<'s, const N: usize> {
+    iter: LispIter<'s>,
+}
+
+impl<'s, const N: usize> Iterator for WindowsConst<'s, N> {
+    type Item = [Atom<'s>; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut fork = self.iter.clone();
+        let mut filled = true;
+        let window: [Option<Atom<'s>>; N] = core::array::from_fn(|_| {
+            let atom = fork.next();
+            filled &= atom.is_some();
+            atom
+        });
+        if !filled {
+            self.iter = fork;
+            return None;
+        }
+        self.iter.skip_expr();
+        Some(window.map(|atom| atom.expect("window was filled")))
+    }
+}
+
+/// Iterator over the atoms of two iterators in alternation, see [`LispIter::interleave`].
+#[derive(Clone)]
+pub struct InterleavedIter<'s> {
+    first: LispIter<'s>,
+    second: LispIter<'s>,
+    /// Whose turn it is.
+    second_next: bool,
+}
+
+impl<'s> Iterator for InterleavedIter<'s> {
+    type Item = Atom<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (turn, other) = match self.second_next {
+            true => (&mut self.second, &mut self.first),
+            false => (&mut self.first, &mut self.second),
+        };
+        self.second_next = !self.second_next;
+        turn.next().or_else(|| other.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, first) = self.first.size_hint();
+        let (_, second) = self.second.size_hint();
+        (0, first.zip(second).map(|(a, b)| a + b))
+    }
+}
+
+/// Iterator over the atoms of two inputs in turn, see [`LispIter::chain_input`].
+#[derive(Clone)]
+pub struct ChainedLispIter<'s> {
+    first: LispIter<'s>,
+    second: LispIter<'s>,
+    in_second: bool,
+}
+
+impl<'s> ChainedLispIter<'s> {
+    /// Whether the first input is exhausted, so atoms now come from the second.
+    pub fn in_second(&self) -> bool {
+        self.in_second
+    }
+
+    /// The iterator of the input currently being read.
+    pub fn current(&self) -> &LispIter<'s> {
+        match self.in_second {
+            true => &self.second,
+            false => &self.first,
+        }
+    }
+}
+
+impl<'s> Iterator for ChainedLispIter<'s> {
+    type Item = Atom<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.in_second {
+            match self.first.next() {
+                Some(atom) => return Some(atom),
+                None => self.in_second = true,
+            }
+        }
+        self.second.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, first) = self.first.size_hint();
+        let (_, second) = self.second.size_hint();
+        (0, first.zip(second).map(|(a, b)| a + b))
+    }
+}
+
+/// Iterator over the pairs of a property list, see [`LispIter::plist`].
+#[derive(Clone)]
+pub struct PlistIter<'s> {
+    iter: LispIter<'s>,
+}
+
+impl<'s> Iterator for PlistIter<'s> {
+    type Item = Result<(Atom<'s>, Option<Atom<'s>>), ParseError<'s>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, span, range) = self.iter.next_ranged()?;
+        if !self.iter.is_keyword(&range) {
+            let offset = span.outer.start;
+            return Some(Err(ParseError::ExpectedKeyword { offset }));
+        }
+        let mut fork = self.iter.clone();
+        let value = match fork.next_ranged() {
+            Some((_, _, range)) if fork.is_keyword(&range) => None,
+            Some((value, ..)) => {
+                self.iter = fork;
+                Some(value)
+            }
+            None => None,
+        };
+        Some(Ok((key, value)))
+    }
+}
+
+/// Iterator over the atoms before a sentinel, see [`LispIter::take_until_identifier`].
+pub struct TakeUntilIter<'i, 's, 'n> {
+    iter: &'i mut LispIter<'s>,
+    name: &'n str,
+}
+
+impl<'s> Iterator for TakeUntilIter<'_, 's, '_> {
+    type Item = Atom<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut fork = self.iter.clone();
+        let atom = fork.next().filter(|atom| !atom.eq_identifier(self.name))?;
+        *self.iter = fork;
+        Some(atom)
+    }
+}
+
+/// Options controlling how strictly [`LispIter`] parses its input.
+///
+/// The default is fully lenient.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseConfig {
+    /// Reject tokens that start like a number (a digit, or `-`/`+` followed by a digit) but
+    /// aren't one, such as `12abc` or `1.2.3`, with [`ParseError::InvalidNumber`].
+    ///
+    /// When unset, such tokens become [`Atom::Identifier`].
+    ///
+    /// Integer literals too large for `i64` are rejected too, with
+    /// [`ParseError::IntegerOverflow`]. Either way they become an inexact [`Atom::Float`].
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, ParseConfig, ParseError};
+    ///
+    /// assert!(LispIter::new("12abc").next().unwrap().eq_identifier("12abc"));
+    ///
+    /// let config = ParseConfig { strict_numbers: true, ..Default::default() };
+    /// let mut iter = LispIter::with_config("12abc -1.2.3 +7 x1", config);
+    /// let error = ParseError::InvalidNumber { offset: 0, token: "12abc" };
+    /// assert_eq!(iter.try_next().unwrap().unwrap_err(), error);
+    /// let error = ParseError::InvalidNumber { offset: 6, token: "-1.2.3" };
+    /// assert_eq!(iter.try_next().unwrap().unwrap_err(), error);
+    /// assert!(iter.try_next().unwrap().unwrap().eq_integer(7));
+    /// assert!(iter.try_next().unwrap().unwrap().eq_identifier("x1"));
+    /// ```
+    pub strict_numbers: bool,
+
+    /// Also accept C-style `/* block */` comments, for formats beyond Scheme and Common
+    /// Lisp.
+    ///
+    /// Unlike `#| |#`, they don't nest: the first `*/` ends the comment. Like any comment,
+    /// one only starts where a token could, so `a/*b*/` is still a single identifier.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, ParseConfig, ParseError};
+    ///
+    /// assert_eq!(LispIter::new("/* c */ a").count(), 4);
+    ///
+    /// let config = ParseConfig { c_comments: true, ..Default::default() };
+    /// let mut iter = LispIter::with_config("/* (a /* b */ c */ a/*b*/", config);
+    /// assert!(iter.next().unwrap().eq_identifier("c"));
+    /// assert!(iter.next().unwrap().eq_identifier("*/"));
+    /// assert!(iter.next().unwrap().eq_identifier("a/*b*/"));
+    /// assert!(iter.next().is_none());
+    ///
+    /// let mut iter = LispIter::with_config("/* open", config);
+    /// let error = ParseError::UnclosedBlockComment { offset: 0 };
+    /// assert_eq!(iter.try_next().unwrap().unwrap_err(), error);
+    /// ```
+    pub c_comments: bool,
+
+    /// Read numbers in scientific notation with a non-negative exponent, like `1e3` or
+    /// `2.5e6`, as [`Atom::Integer`] when they're whole and within `i64` range.
+    ///
+    /// The value is that of the parsed float, so beyond 2^53 it may already be rounded.
+    /// Other floats, including `2.0` and `1e-3`, are left alone.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter, ParseConfig};
+    ///
+    /// assert_eq!(LispIter::new("1e3").next(), Some(Atom::Float(1000.0)));
+    ///
+    /// let config = ParseConfig { exponent_integers: true, ..Default::default() };
+    /// let atoms: Vec<_> = LispIter::with_config("1e3 2.5e6 -4E2 1.5e0 1e-3 2.0 1e19", config)
+    ///     .collect();
+    /// assert_eq!(
+    ///     atoms,
+    ///     [
+    ///         Atom::Integer(1000),
+    ///         Atom::Integer(2_500_000),
+    ///         Atom::Integer(-400),
+    ///         Atom::Float(1.5),
+    ///         Atom::Float(1e-3),
+    ///         Atom::Float(2.0),
+    ///         Atom::Float(1e19),
+    ///     ]
+    /// );
+    /// ```
+    pub exponent_integers: bool,
+
+    /// Read Scheme's `'datum` shorthand as the list `(quote datum)`.
+    ///
+    /// The [`Atom::List`] iterates over the `'` itself, read as the identifier `quote`,
+    /// then the datum, and equals the list written out in full. A `'` only starts a quote
+    /// form when a datum directly follows it; otherwise, as when unset, it's part of an
+    /// identifier.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, ParseConfig};
+    ///
+    /// let config = ParseConfig { quote_forms: true, ..Default::default() };
+    /// let mut iter = LispIter::with_config("(f '(a b) 'c)", config);
+    /// let mut call = iter.next().unwrap().into_list_iter().unwrap();
+    /// assert!(call.next().unwrap().eq_identifier("f"));
+    /// assert_eq!(call.next(), LispIter::new("(quote (a b))").next());
+    /// let mut quoted = call.next().unwrap().into_list_iter().unwrap();
+    /// assert!(quoted.next().unwrap().eq_identifier("quote"));
+    /// assert!(quoted.next().unwrap().eq_identifier("c"));
+    /// assert!(quoted.next().is_none() && call.next().is_none());
+    /// ```
+    pub quote_forms: bool,
+}
+
+/// Error reported by [`LispIter::try_next`].
+///
+/// Offsets are in bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError<'a> {
+    /// A numeric-looking token failed to parse as an integer or a float.
+    InvalidNumber { offset: usize, token: &'a str },
+
+    /// An integer literal beyond the range of `i64`. It is read as an [`Atom::Float`].
+    IntegerOverflow { offset: usize },
+
+    /// A `(`, `[` or `{` without its matching closer. The list, vector or map runs to the
+    /// end of input.
+    UnclosedList { offset: usize },
+
+    /// A `"` without its closing `"`. The quote runs to the end of input.
+    UnclosedQuote { offset: usize },
+
+    /// A `#"..."` byte string that isn't an even number of hex digits. It is skipped.
+    InvalidBytes { offset: usize },
+
+    /// A `#|` without its closing `|#`, or a `/*` without its `*/`. The comment runs to the
+    /// end of input.
+    UnclosedBlockComment { offset: usize },
+
+    /// A `{` whose contents can't be split into key-value pairs. The map is still returned,
+    /// and its last key is dropped by [`Atom::as_map_iter`].
+    OddMapLength { offset: usize },
+
+    /// A `)`, `]` or `}` that doesn't close anything. It is skipped.
+    UnexpectedCloser { offset: usize },
+
+    /// A closer of the wrong kind, like the `]` of `(a]`, at `offset`. It still closes the
+    /// innermost open bracket, as the lenient parse does.
+    ///
+    /// ```
+    /// use lisp_iter::{check, is_complete, Atom, Completeness, LispIter, ParseError};
+    ///
+    /// for (input, expected, found) in [("(a]", ')', ']'), ("[a)", ']', ')'), ("{a]", '}', ']')] {
+    ///     let error = ParseError::MismatchedCloser { offset: 2, expected, found };
+    ///     let mut iter = LispIter::new(input);
+    ///     let (Some(Atom::List(mut items) | Atom::Vector(mut items) | Atom::Map(mut items))) =
+    ///         iter.next()
+    ///     else {
+    ///         panic!("{input} should still parse as one container");
+    ///     };
+    ///     assert!(items.next().unwrap().eq_identifier("a"));
+    ///     assert!(iter.next().is_none());
+    ///     assert_eq!(LispIter::new(input).try_next().unwrap().unwrap_err(), error);
+    ///     assert!(check(input).any(|diagnostic| diagnostic.error == error));
+    ///     assert_eq!(LispIter::strict_mode(input).next().unwrap().unwrap_err(), error);
+    ///     assert_eq!(is_complete(input), Completeness::Invalid);
+    /// }
+    ///
+    /// let error = ParseError::MismatchedCloser { offset: 3, expected: ']', found: ')' };
+    /// assert_eq!(check("([a) b)").next().unwrap().error, error);
+    /// assert_eq!(LispIter::strict_mode("([a) b)").next().unwrap().unwrap_err(), error);
+    /// assert_eq!(is_complete("([a) b)"), Completeness::Invalid);
+    ///
+    /// let deep = "[".repeat(100) + &"(".repeat(100) + &")".repeat(100) + &"]".repeat(99) + ")";
+    /// let error = ParseError::MismatchedCloser { offset: 399, expected: ']', found: ')' };
+    /// assert_eq!(check(&deep).next().unwrap().error, error);
+    /// assert_eq!(is_complete(&deep), Completeness::Invalid);
+    /// assert_eq!(is_complete(&deep[..399]), Completeness::Incomplete);
+    /// ```
+    MismatchedCloser {
+        offset: usize,
+        expected: char,
+        found: char,
+    },
+
+    /// An atom of the wrong kind, as reported by the `expect_*` helpers on [`LispIter`].
+    UnexpectedAtom { expected: AtomKind, got: AtomKind },
+
+    /// The input ran out where an atom, possibly of a given kind, was expected.
+    UnexpectedEnd { expected: Option<AtomKind> },
+
+    /// An atom other than a `:keyword` where [`LispIter::plist`] expected a key. It is
+    /// skipped.
+    ExpectedKeyword { offset: usize },
+}
+
+impl core::fmt::Display for ParseError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidNumber { offset, token } => {
+                write!(f, "invalid number `{}` at byte {}", token, offset)
+            }
+            Self::IntegerOverflow { offset } => {
+                write!(f, "integer out of range at byte {}", offset)
+            }
+            Self::UnclosedList { offset } => write!(f, "unclosed list at byte {}", offset),
+            Self::UnclosedQuote { offset } => write!(f, "unclosed quote at byte {}", offset),
+            Self::InvalidBytes { offset } => write!(f, "invalid byte string at byte {}", offset),
+            Self::UnclosedBlockComment { offset } => {
+                write!(f, "unclosed block comment at byte {}", offset)
+            }
+            Self::OddMapLength { offset } => {
+                write!(f, "map with an odd number of elements at byte {}", offset)
+            }
+            Self::UnexpectedCloser { offset } => {
+                write!(f, "unexpected closing bracket at byte {}", offset)
+            }
+            Self::MismatchedCloser {
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected `{}`, found `{}` at byte {}",
+                expected, found, offset
+            ),
+            Self::UnexpectedAtom { expected, got } => {
+                write!(f, "expected {}, found {}", expected, got)
+            }
+            Self::UnexpectedEnd {
+                expected: Some(expected),
+            } => write!(f, "expected {}, found end of input", expected),
+            Self::UnexpectedEnd { expected: None } => f.write_str("unexpected end of input"),
+            Self::ExpectedKeyword { offset } => write!(f, "expected keyword at byte {}", offset),
+        }
+    }
+}
+
+impl core::error::Error for ParseError<'_> {}
+
+/// Error returned by the `expect_*` helpers on [`Atom`]: the atom isn't of the expected
+/// kind.
+///
+/// The atom is handed back, so a caller can try another kind without re-parsing:
+///
+/// ```
+/// use lisp_iter::{AtomKind, LispIter};
+///
+/// let atom = LispIter::new("1.5").next().unwrap();
+/// let err = atom.expect_integer().unwrap_err();
+/// assert_eq!(err.expected, AtomKind::Integer);
+/// assert_eq!(err.to_string(), "expected integer, found float 1.5");
+/// assert_eq!(err.found.expect_float().unwrap(), 1.5);
+///
+/// let err = LispIter::new("(a)").next().unwrap().expect_quote().unwrap_err();
+/// assert_eq!(err.to_string(), "expected quote, found list");
+/// assert!(err.found.expect_list().is_ok());
+/// ```
+#[derive(Clone, Debug)]
+pub struct TypeMismatch<'a> {
+    pub expected: AtomKind,
+    /// The atom that was found instead.
+    pub found: Atom<'a>,
+}
+
+impl core::fmt::Display for TypeMismatch<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "expected {}, found {}", self.expected, self.found.kind())?;
+        match &self.found {
+            Atom::Identifier(s) | Atom::Quote(s) => write!(f, " {:?}", s),
+            Atom::Integer(v) => write!(f, " {}", v),
+            Atom::Float(v) => write!(f, " {}", v),
+            Atom::Float32(v) => write!(f, " {}f32", v),
+            Atom::Rational(n, d) => write!(f, " {}/{}", n, d),
+            Atom::Bytes(hex) => write!(f, " #\"{}\"", hex.escape_ascii()),
+            Atom::List(_) | Atom::Vector(_) | Atom::Map(_) => Ok(()),
+        }
+    }
+}
+
+impl core::error::Error for TypeMismatch<'_> {}
+
+impl<'a> From<TypeMismatch<'a>> for ParseError<'a> {
+    fn from(e: TypeMismatch<'a>) -> Self {
+        ParseError::UnexpectedAtom {
+            expected: e.expected,
+            got: e.found.kind(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum Atom<'a> {
+    /// Any unquoted word.
+    ///
+    /// A word ends at whitespace, a bracket, a `"` or a `;`, the same at the top level as
+    /// inside a list, so `a(b)` is the identifier `a` followed by a list, and `c;d` is `c`
+    /// followed by a comment.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("a(b) x\"y\" c;d\ne]");
+    /// assert!(iter.next().unwrap().eq_identifier("a"));
+    /// assert!(iter.next().unwrap().into_list_iter().unwrap().next().unwrap().eq_identifier("b"));
+    /// assert!(iter.next().unwrap().eq_identifier("x"));
+    /// assert!(iter.next().unwrap().eq_quote("y"));
+    /// assert!(iter.next().unwrap().eq_identifier("c"));
+    /// assert!(iter.next().unwrap().eq_identifier("e"));
+    /// assert!(iter.next().is_none());
+    ///
+    /// let list = lisp_iter::LispIter::new("(x a;b\n)").next().unwrap();
+    /// assert_eq!(list.as_list().unwrap().count(), 2);
+    /// ```
+    Identifier(&'a str),
+
+    /// Any string between two " "
+    ///
+    /// Note: quotes are unescaped i.e. \n \r and other escape sequences aren't taken into account.
+    /// This is to prevent dynamic heap allocations.
+    ///
+    /// Raw strings are also accepted, Rust style: `r"..."`, or `r#"..."#` with any number of
+    /// `#`s to allow `"` inside.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, ParseError};
+    ///
+    /// let mut iter = LispIter::new(r####"r"a\b" r#"say "hi""# r##"a "# b"## r rx"####);
+    /// assert!(iter.next().unwrap().eq_quote(r"a\b"));
+    /// assert!(iter.next().unwrap().eq_quote(r#"say "hi""#));
+    /// assert!(iter.next().unwrap().eq_quote(r##"a "# b"##));
+    /// assert!(iter.next().unwrap().eq_identifier("r"));
+    /// assert!(iter.next().unwrap().eq_identifier("rx"));
+    ///
+    /// let error = LispIter::new(r##"r#"a" b"##).try_next().unwrap().unwrap_err();
+    /// assert_eq!(error, ParseError::UnclosedQuote { offset: 0 });
+    /// ```
+    Quote(&'a str),
+
+    /// Signed 64-bit integer, optionally prefixed with `-` or `+`.
+    ///
+    /// A literal beyond `i64` range, like `9999999999999999999`, is read as the nearest
+    /// [`Atom::Float`] instead, and loses precision. With [`ParseConfig::strict_numbers`]
+    /// it's also reported as a [`ParseError::IntegerOverflow`].
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter, ParseConfig, ParseError};
+    ///
+    /// let big = "9999999999999999999";
+    /// assert!(matches!(LispIter::new(big).next(), Some(Atom::Float(v)) if v == 1e19));
+    /// let config = ParseConfig { strict_numbers: true, ..ParseConfig::default() };
+    /// let err = LispIter::with_config(big, config).try_next().unwrap().unwrap_err();
+    /// assert_eq!(err, ParseError::IntegerOverflow { offset: 0 });
+    ///
+    /// let mut iter = LispIter::new("+42 -42 +1.5 + +x");
+    /// assert!(iter.next().unwrap().eq_integer(42));
+    /// assert!(iter.next().unwrap().eq_integer(-42));
+    /// assert!(iter.next().unwrap().eq_float(1.5));
+    /// assert!(iter.next().unwrap().eq_identifier("+"));
+    /// assert!(iter.next().unwrap().eq_identifier("+x"));
+    /// ```
+    Integer(i64),
+
+    /// 64-bit floating-point number.
+    Float(f64),
+
+    /// 32-bit floating-point number, written with an `f32` suffix like `3.14f32`, for
+    /// targets with only single-precision hardware.
+    ///
+    /// Without the suffix a float is always an [`Atom::Float`]. [`Atom::as_f64`] widens
+    /// this one exactly, and it converts to `f32` as is.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("3.14f32 3.14");
+    /// assert_eq!(iter.next().unwrap().as_float32(), Some(3.14));
+    /// assert_eq!(iter.next().unwrap().as_float(), Some(3.14));
+    /// ```
+    Float32(f32),
+
+    /// Exact rational such as `3/4` or `-1/2`, as numerator and denominator.
+    ///
+    /// It's stored as written, not reduced, so `2/4` holds `(2, 4)` and doesn't equal
+    /// `1/2`. A zero denominator isn't a rational: `1/0` is an [`Atom::Identifier`], or a
+    /// [`ParseError::InvalidNumber`] with [`ParseConfig::strict_numbers`].
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter, ParseConfig, ParseError};
+    ///
+    /// let atoms: Vec<_> = LispIter::new("3/4 -1/2 2/4 1/0 1/-2").collect();
+    /// assert_eq!(atoms[0].as_rational(), Some((3, 4)));
+    /// assert_eq!(atoms[1], Atom::Rational(-1, 2));
+    /// assert_eq!(atoms[2], Atom::Rational(2, 4));
+    /// assert_ne!(atoms[2], Atom::Rational(1, 2));
+    /// assert!(atoms[3].eq_identifier("1/0"));
+    /// assert!(atoms[4].eq_identifier("1/-2"));
+    ///
+    /// let config = ParseConfig { strict_numbers: true, ..Default::default() };
+    /// let error = LispIter::with_config("1/0", config).try_next().unwrap().unwrap_err();
+    /// assert_eq!(error, ParseError::InvalidNumber { offset: 0, token: "1/0" });
+    /// ```
+    Rational(i64, u64),
+
+    /// Binary data written as hex digits, like `#"DEADBEEF"`.
+    ///
+    /// Decoding would need somewhere to put the bytes, so this holds the hex digits
+    /// themselves, as ASCII; [`Atom::decode_bytes`] decodes them on the fly. The literal
+    /// must be an even number of hex digits in either case, else it's a
+    /// [`ParseError::InvalidBytes`]. R7RS `#vu8(...)` bytevectors aren't supported, since
+    /// their bytes can't be borrowed from the input.
+    ///
+    /// ```
+    /// use lisp_iter::{sexpr_eq, LispIter};
+    ///
+    /// let bytes = LispIter::new("#\"CAFE\"").next().unwrap();
+    /// assert_eq!(bytes.as_bytes(), Some(&b"CAFE"[..]));
+    /// assert!(bytes == bytes.clone());
+    /// assert!(sexpr_eq("#\"CAFE\"", "#\"CAFE\""));
+    /// assert!(sexpr_eq("(f #\"00ff\")", "(f ; data\n #\"00ff\")"));
+    /// assert!(!sexpr_eq("#\"CAFE\"", "#\"CAFF\""));
+    /// ```
+    Bytes(&'a [u8]),
+
+    /// Anything between two ( )
+    ///
+    /// Holds another [`LispIter`]
+    List(LispIter<'a>),
+
+    /// Anything between two [ ], as used for vectors by Clojure and others.
+    ///
+    /// Holds another [`LispIter`] over the contents, just like [`Atom::List`]. Brackets of
+    /// all kinds nest together, so a `)` closes a vector left open inside a list; the wrong
+    /// kind of closer is reported as a [`ParseError::MismatchedCloser`].
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter, ParseError};
+    ///
+    /// let mut atoms = LispIter::new("[1 [2 3] (4)] []");
+    /// let Some(Atom::Vector(mut items)) = atoms.next() else { panic!() };
+    /// assert!(items.next().unwrap().eq_integer(1));
+    /// assert!(matches!(items.next(), Some(Atom::Vector(inner)) if inner.clone().count() == 2));
+    /// assert!(matches!(items.next(), Some(Atom::List(_))));
+    /// assert!(items.next().is_none());
+    /// assert!(matches!(atoms.next(), Some(Atom::Vector(empty)) if empty.is_empty()));
+    ///
+    /// let error = LispIter::new("[a)").try_next().unwrap().unwrap_err();
+    /// assert_eq!(error, ParseError::MismatchedCloser { offset: 2, expected: ']', found: ')' });
+    /// ```
+    Vector(LispIter<'a>),
+
+    /// Anything between two { }, as used for hash maps by Clojure.
+    ///
+    /// The [`LispIter`] yields the keys and values alternately; see [`Atom::as_map_iter`]
+    /// to get them in pairs. An odd number of elements is a [`ParseError::OddMapLength`].
+    Map(LispIter<'a>),
+}
+
+/// The variant of an [`Atom`], without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AtomKind {
+    Identifier,
+    Quote,
+    Integer,
+    Float,
+    Float32,
+    Rational,
+    Bytes,
+    List,
+    Vector,
+    Map,
+}
+
+impl core::fmt::Display for AtomKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::Identifier => "identifier",
+            Self::Quote => "quote",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Float32 => "float32",
+            Self::Rational => "rational",
+            Self::Bytes => "bytes",
+            Self::List => "list",
+            Self::Vector => "vector",
+            Self::Map => "map",
+        })
+    }
+}
+
+impl<'a> Atom<'a> {
+    /// Returns which variant this atom is.
+    ///
+    /// ```
+    /// use lisp_iter::{AtomKind, AtomKind::*, LispIter};
+    ///
+    /// let kinds: Vec<_> = LispIter::new("x \"s\" 1 1.0 1/2 #\"00\" () [] {}")
+    ///     .map(|atom| atom.kind())
+    ///     .collect();
+    /// assert_eq!(kinds, [Identifier, Quote, Integer, Float, Rational, Bytes, List, Vector, Map]);
+    /// assert_eq!(AtomKind::Float.to_string(), "float");
+    /// ```
+    pub fn kind(&self) -> AtomKind {
+        match self {
+            Self::Identifier(_) => AtomKind::Identifier,
+            Self::Quote(_) => AtomKind::Quote,
+            Self::Integer(_) => AtomKind::Integer,
+            Self::Float(_) => AtomKind::Float,
+            Self::Float32(_) => AtomKind::Float32,
+            Self::Rational(..) => AtomKind::Rational,
+            Self::Bytes(_) => AtomKind::Bytes,
+            Self::List(_) => AtomKind::List,
+            Self::Vector(_) => AtomKind::Vector,
+            Self::Map(_) => AtomKind::Map,
+        }
+    }
+
+    /// Returns the text of an [`Atom::Identifier`], or `None` for any other atom.
+    ///
+    /// The `as_*` accessors chain well with `?` in functions returning `Option`:
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// fn resize(form: &Atom) -> Option<(i64, i64)> {
+    ///     let mut args = form.as_list()?;
+    ///     if args.next()?.as_identifier()? != "resize" {
+    ///         return None;
+    ///     }
+    ///     Some((args.next()?.as_integer()?, args.next()?.as_integer()?))
+    /// }
+    ///
+    /// let form = LispIter::new("(resize 800 600)").next().unwrap();
+    /// assert_eq!(resize(&form), Some((800, 600)));
+    /// ```
+    pub fn as_identifier(&self) -> Option<&'a str> {
+        match self {
+            Self::Identifier(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the contents of an [`Atom::Quote`], or `None` for any other atom.
+    pub fn as_quote(&self) -> Option<&'a str> {
+        match self {
+            Self::Quote(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an [`Atom::Integer`], or `None` for any other atom.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an [`Atom::Float`], or `None` for any other atom.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Self::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an [`Atom::Float32`], or `None` for any other atom.
+    pub fn as_float32(&self) -> Option<f32> {
+        match self {
+            Self::Float32(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Returns the numerator and denominator of an [`Atom::Rational`], or `None` for any
+    /// other atom.
+    pub fn as_rational(&self) -> Option<(i64, u64)> {
+        match self {
+            Self::Rational(n, d) => Some((*n, *d)),
+            _ => None,
+        }
+    }
+
+    /// Returns the hex digits of an [`Atom::Bytes`], or `None` for any other atom.
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            Self::Bytes(hex) => Some(hex),
+            _ => None,
+        }
+    }
+
+    /// Returns the decoded bytes of an [`Atom::Bytes`], or `None` for any other atom.
+    ///
+    /// ```
+    /// let atom = lisp_iter::LispIter::new("#\"CAFE01\"").next().unwrap();
+    /// assert!(atom.decode_bytes().unwrap().eq([0xCA, 0xFE, 0x01]));
+    /// ```
+    pub fn decode_bytes(&self) -> Option<impl Iterator<Item = u8> + 'a> {
+        let digit = |b: u8| (b as char).to_digit(16).unwrap_or(0) as u8;
+        Some(
+            self.as_bytes()?
+                .chunks_exact(2)
+                .map(move |pair| digit(pair[0]) << 4 | digit(pair[1])),
+        )
+    }
+
+    /// Returns the value of an [`Atom::Integer`], [`Atom::Float`] or [`Atom::Float32`] as
+    /// an `f64`, or `None` for any other atom.
+    ///
+    /// Integers up to 2^53 in magnitude convert exactly. Larger ones round to the nearest
+    /// representable `f64`, ties to even, as with `as`. An `f32` always widens exactly.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// let mut atoms = LispIter::new(r#"3 0.25 "3""#);
+    /// assert_eq!(atoms.next().unwrap().as_f64(), Some(3.0));
+    /// assert_eq!(atoms.next().unwrap().as_f64(), Some(0.25));
+    /// assert_eq!(atoms.next().unwrap().as_f64(), None);
+    ///
+    /// assert_eq!(Atom::Float32(0.1).as_f64(), Some(0.1f32 as f64));
+    /// assert_eq!(Atom::Integer((1 << 53) + 1).as_f64(), Some((1u64 << 53) as f64));
+    /// assert_eq!(Atom::Integer(i64::MIN).as_f64(), Some(-9_223_372_036_854_775_808.0));
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Integer(v) => Some(*v as f64),
+            Self::Float(v) => Some(*v),
+            Self::Float32(v) => Some(f64::from(*v)),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an [`Atom::Integer`], or of an [`Atom::Float`] holding a whole
+    /// number within `i64` range, or `None` otherwise.
+    ///
+    /// A float is only accepted if it converts without any loss: `2.0` and `-0.0` do, while
+    /// `2.5`, `1e20`, infinities and NaN don't. The range is `-2^63 <= v < 2^63`, so
+    /// `i64::MIN as f64` is accepted but `i64::MAX as f64`, which rounds up to 2^63, isn't.
+    ///
+    /// ```
+    /// use lisp_iter::Atom;
+    ///
+    /// assert_eq!(Atom::Integer(7).as_i64_lossy(), Some(7));
+    /// assert_eq!(Atom::Float(2.0).as_i64_lossy(), Some(2));
+    /// assert_eq!(Atom::Float(-0.0).as_i64_lossy(), Some(0));
+    /// assert_eq!(Atom::Float(i64::MIN as f64).as_i64_lossy(), Some(i64::MIN));
+    ///
+    /// for v in [2.5, 1e20, i64::MAX as f64, f64::INFINITY, f64::NEG_INFINITY, f64::NAN] {
+    ///     assert_eq!(Atom::Float(v).as_i64_lossy(), None, "{v}");
+    /// }
+    /// assert_eq!(Atom::Float32(2.0).as_i64_lossy(), None);
+    /// assert_eq!(Atom::Quote("7").as_i64_lossy(), None);
+    /// ```
+    pub fn as_i64_lossy(&self) -> Option<i64> {
+        const LIMIT: f64 = 9_223_372_036_854_775_808.0; // 2^63
+        match self {
+            Self::Integer(v) => Some(*v),
+            Self::Float(v) if (-LIMIT..LIMIT).contains(v) && *v as i64 as f64 == *v => {
+                Some(*v as i64)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a clone of the iterator of an [`Atom::List`], or `None` for any other atom.
+    ///
+    /// The atom itself isn't consumed; see [`Atom::into_list_iter`] to take the iterator.
+    pub fn as_list(&self) -> Option<LispIter<'a>> {
+        match self {
+            Self::List(iter) => Some(iter.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` for an [`Atom::Identifier`].
+    ///
+    /// Each atom answers to exactly one `as_*` accessor and the matching `is_*` predicate;
+    /// every other accessor returns `None` and every other predicate `false`. An
+    /// [`Atom::Vector`] has no accessor of its own, only [`Atom::is_vector`]:
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// let source = r#"x "q" 1 1.5 2.5f32 1/2 #"FF" (l) [v] {k v}"#;
+    /// let atoms: Vec<_> = LispIter::new(source).collect();
+    /// assert_eq!(atoms.len(), 10);
+    /// for (i, atom) in atoms.iter().enumerate() {
+    ///     let accessors = [
+    ///         atom.as_identifier().is_some(),
+    ///         atom.as_quote().is_some(),
+    ///         atom.as_integer().is_some(),
+    ///         atom.as_float().is_some(),
+    ///         atom.as_float32().is_some(),
+    ///         atom.as_rational().is_some(),
+    ///         atom.as_bytes().is_some(),
+    ///         atom.as_list().is_some(),
+    ///         matches!(atom, Atom::Vector(_)),
+    ///         atom.as_map_iter().is_some(),
+    ///     ];
+    ///     let predicates = [
+    ///         atom.is_identifier(),
+    ///         atom.is_quote(),
+    ///         atom.is_integer(),
+    ///         atom.is_float(),
+    ///         atom.is_float32(),
+    ///         atom.is_rational(),
+    ///         atom.is_bytes(),
+    ///         atom.is_list(),
+    ///         atom.is_vector(),
+    ///         atom.is_map(),
+    ///     ];
+    ///     let expected: [bool; 10] = core::array::from_fn(|j| j == i);
+    ///     assert_eq!(accessors, expected, "{atom:?}");
+    ///     assert_eq!(predicates, expected, "{atom:?}");
+    ///     assert_eq!(atom.as_f64().is_some(), (2..=4).contains(&i), "{atom:?}");
+    ///     assert_eq!(atom.as_i64_lossy().is_some(), i == 2, "{atom:?}");
+    /// }
+    ///
+    /// assert_eq!(atoms[0].as_identifier(), Some("x"));
+    /// assert_eq!(atoms[1].as_quote(), Some("q"));
+    /// assert_eq!(atoms[2].as_integer(), Some(1));
+    /// assert_eq!(atoms[3].as_float(), Some(1.5));
+    /// assert_eq!(atoms[4].as_float32(), Some(2.5));
+    /// assert_eq!(atoms[5].as_rational(), Some((1, 2)));
+    /// assert_eq!(atoms[6].as_bytes(), Some(&b"FF"[..]));
+    /// ```
+    pub fn is_identifier(&self) -> bool {
+        self.kind() == AtomKind::Identifier
+    }
+
+    pub fn is_quote(&self) -> bool {
+        self.kind() == AtomKind::Quote
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.kind() == AtomKind::Integer
+    }
+
+    pub fn is_float(&self) -> bool {
+        self.kind() == AtomKind::Float
+    }
+
+    pub fn is_float32(&self) -> bool {
+        self.kind() == AtomKind::Float32
+    }
+
+    pub fn is_rational(&self) -> bool {
+        self.kind() == AtomKind::Rational
+    }
+
+    pub fn is_bytes(&self) -> bool {
+        self.kind() == AtomKind::Bytes
+    }
+
+    pub fn is_list(&self) -> bool {
+        self.kind() == AtomKind::List
+    }
+
+    /// Returns `true` for a list without elements, like `()` or `( ; comment\n)`.
+    ///
+    /// Takes time linear in the whitespace and comments before the first element or the
+    /// closing `)`, since those are skipped to find out; nothing is parsed past them.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// assert!(LispIter::new("( ; nothing\n)").next().unwrap().is_empty_list());
+    /// assert!(LispIter::new("( #| nothing |# )").next().unwrap().is_empty_list());
+    /// assert!(!LispIter::new("(x)").next().unwrap().is_empty_list());
+    /// assert!(!LispIter::new("[]").next().unwrap().is_empty_list());
+    /// ```
+    pub fn is_empty_list(&self) -> bool {
+        matches!(self, Atom::List(list) if list.is_empty())
+    }
+
+    pub fn is_vector(&self) -> bool {
+        self.kind() == AtomKind::Vector
+    }
+
+    pub fn is_map(&self) -> bool {
+        self.kind() == AtomKind::Map
+    }
+
+    /// Returns an iterator over the key-value pairs of an [`Atom::Map`], or `None` for any
+    /// other atom.
+    ///
+    /// Works on a clone of the inner iterator, like [`Atom::as_list`]. If the map has an
+    /// odd number of elements, the last key has no value and is dropped.
+    ///
+    /// ```
+    /// let map = lisp_iter::LispIter::new("{:width 800 :title \"demo\"}").next().unwrap();
+    /// let mut pairs = map.as_map_iter().unwrap();
+    /// let (key, value) = pairs.next().unwrap();
+    /// assert!(key.eq_quote("width") && value.eq_integer(800));
+    /// ```
+    pub fn as_map_iter(&self) -> Option<MapIter<'a>> {
+        match self {
+            Self::Map(iter) => Some(MapIter { iter: iter.clone() }),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this is an [`Atom::Identifier`] with the text `name`.
+    ///
+    /// The `eq_*` helpers check the kind as well as the value, so an identifier never
+    /// equals a quote with the same text, nor an integer a float with the same value:
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// for (i, atom) in LispIter::new(r#"x "x" 1 1.0"#).enumerate() {
+    ///     assert_eq!(atom.eq_identifier("x"), i == 0);
+    ///     assert_eq!(atom.eq_quote("x"), i == 1);
+    ///     assert_eq!(atom.eq_integer(1), i == 2);
+    ///     assert_eq!(atom.eq_float(1.0), i == 3);
+    ///     assert!(!atom.eq_identifier("y"));
+    /// }
+    /// ```
+    #[inline]
+    pub fn eq_identifier(&self, name: &str) -> bool {
+        matches!(self, Self::Identifier(s) if *s == name)
+    }
+
+    /// Returns `true` if this is the symbol `name`. Same as [`Atom::eq_identifier`], under
+    /// the name Lisp gives it.
+    ///
+    /// A `:keyword` is an [`Atom::Quote`], so it's never a symbol.
+    #[inline]
+    pub fn is_symbol(&self, name: &str) -> bool {
+        self.eq_identifier(name)
+    }
+
+    /// Returns `true` if this is an [`Atom::Quote`] with the contents `s`.
+    #[inline]
+    pub fn eq_quote(&self, s: &str) -> bool {
+        matches!(self, Self::Quote(q) if *q == s)
+    }
+
+    /// Returns `true` if this is an [`Atom::Integer`] with the value `v`.
+    #[inline]
+    pub fn eq_integer(&self, v: i64) -> bool {
+        matches!(self, Self::Integer(i) if *i == v)
+    }
+
+    /// Returns `true` if this is an [`Atom::Float`] equal to `v`.
+    ///
+    /// Compares with `==`, so NaN never matches and `0.0` matches `-0.0`.
+    ///
+    /// ```
+    /// use lisp_iter::Atom;
+    ///
+    /// assert!(Atom::Float(0.0).eq_float(-0.0));
+    /// assert!(!Atom::Float(f64::NAN).eq_float(f64::NAN));
+    /// ```
+    #[inline]
+    pub fn eq_float(&self, v: f64) -> bool {
+        matches!(self, Self::Float(f) if *f == v)
+    }
+
+    fn mismatch<T>(self, expected: AtomKind) -> Result<T, TypeMismatch<'a>> {
+        Err(TypeMismatch {
+            expected,
+            found: self,
+        })
+    }
+
+    /// Returns the text of an [`Atom::Identifier`], or a [`TypeMismatch`] carrying the atom.
+    pub fn expect_identifier(self) -> Result<&'a str, TypeMismatch<'a>> {
+        match self {
+            Self::Identifier(s) => Ok(s),
+            atom => atom.mismatch(AtomKind::Identifier),
+        }
+    }
+
+    /// Returns the contents of an [`Atom::Quote`], or a [`TypeMismatch`] carrying the atom.
+    pub fn expect_quote(self) -> Result<&'a str, TypeMismatch<'a>> {
+        match self {
+            Self::Quote(s) => Ok(s),
+            atom => atom.mismatch(AtomKind::Quote),
+        }
+    }
+
+    /// Returns the value of an [`Atom::Integer`], or a [`TypeMismatch`] carrying the atom.
+    pub fn expect_integer(self) -> Result<i64, TypeMismatch<'a>> {
+        match self {
+            Self::Integer(v) => Ok(v),
+            atom => atom.mismatch(AtomKind::Integer),
+        }
+    }
+
+    /// Returns the value of an [`Atom::Float`], or a [`TypeMismatch`] carrying the atom.
+    pub fn expect_float(self) -> Result<f64, TypeMismatch<'a>> {
+        match self {
+            Self::Float(v) => Ok(v),
+            atom => atom.mismatch(AtomKind::Float),
+        }
+    }
+
+    /// Returns the iterator of an [`Atom::List`], or a [`TypeMismatch`] carrying the atom.
+    pub fn expect_list(self) -> Result<LispIter<'a>, TypeMismatch<'a>> {
+        match self {
+            Self::List(iter) => Ok(iter),
+            atom => atom.mismatch(AtomKind::List),
+        }
+    }
+
+    /// Returns the iterator of a [`Atom::List`], or `None` for any other atom.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut atoms = LispIter::new("(a b) [a b] a");
+    /// let mut list = atoms.next().unwrap().into_list_iter().unwrap();
+    /// assert!(matches!(list.next().unwrap(), lisp_iter::Atom::Identifier("a")));
+    /// assert!(atoms.next().unwrap().into_list_iter().is_none());
+    /// assert!(atoms.next().unwrap().into_list_iter().is_none());
+    /// ```
+    pub fn into_list_iter(self) -> Option<LispIter<'a>> {
+        match self {
+            Self::List(iter) => Some(iter),
+            _ => None,
+        }
+    }
+
+    /// Returns the iterator of an [`Atom::List`], or a [`NotAList`] carrying any other
+    /// atom.
+    ///
+    /// Unlike `into_iter`, this doesn't treat a single atom as a list of itself.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut atoms = LispIter::new("(a b) a");
+    /// assert_eq!(atoms.next().unwrap().try_into_iter().unwrap().count(), 2);
+    /// let err = atoms.next().unwrap().try_into_iter().err().unwrap();
+    /// assert_eq!(err.to_string(), "expected list, found identifier");
+    /// ```
+    pub fn try_into_iter(self) -> Result<LispIter<'a>, NotAList<'a>> {
+        match self {
+            Self::List(iter) => Ok(iter),
+            atom => Err(NotAList { atom }),
+        }
+    }
+
+    /// Returns the iterator of a [`Atom::List`], or `default` for any other atom.
+    ///
+    /// Pass `LispIter::new("")` to treat a missing list as an empty one.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut atoms = LispIter::new("(a b) nil");
+    /// assert_eq!(atoms.next().unwrap().into_list_iter_or(LispIter::new("")).count(), 2);
+    /// assert_eq!(atoms.next().unwrap().into_list_iter_or(LispIter::new("")).count(), 0);
+    /// ```
+    pub fn into_list_iter_or(self, default: LispIter<'a>) -> LispIter<'a> {
+        self.into_list_iter().unwrap_or(default)
+    }
+
+    /// Splits a list into its first element and an iterator over the rest.
+    ///
+    /// Returns `None` for an empty list. Like [`AtomIter`], a non-list atom acts as a list
+    /// of itself: it's returned as the head, with nothing after it.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut forms = LispIter::new("(add 1 2 3) (neg 4) () done");
+    /// let mut results = Vec::new();
+    /// for form in forms.by_ref().take(2) {
+    ///     let (command, args) = form.split_head().unwrap();
+    ///     let mut args = args.filter_map(|arg| arg.as_integer());
+    ///     results.push(match command.as_identifier() {
+    ///         Some("add") => args.sum(),
+    ///         Some("neg") => -args.next().unwrap(),
+    ///         _ => unreachable!(),
+    ///     });
+    /// }
+    /// assert_eq!(results, [6, -4]);
+    ///
+    /// assert!(forms.next().unwrap().split_head().is_none());
+    ///
+    /// let (head, mut rest) = forms.next().unwrap().split_head().unwrap();
+    /// assert!(head.eq_identifier("done"));
+    /// assert!(rest.next().is_none());
+    /// ```
+    pub fn split_head(self) -> Option<(Atom<'a>, LispIter<'a>)> {
+        match self {
+            Self::List(mut iter) => Some((iter.next()?, iter)),
+            atom => Some((atom, LispIter::new(""))),
+        }
+    }
+
+    /// Looks `key` up in an association list such as `((width 800) (title "demo"))`,
+    /// returning the second element of the first entry whose head is the identifier or
+    /// `:keyword` `key`.
+    ///
+    /// Entries that aren't lists or have fewer than two elements are skipped. Returns `None`
+    /// for a non-list atom. See [`Atom::assoc_all`] for repeated keys.
+    ///
+    /// Each call rescans the list from the start, parsing the entries it passes, so looking
+    /// up k keys in n entries is O(k·n). That's fine for a config section; keep the list
+    /// atom around and query it rather than reparsing the whole document per key:
+    ///
+    /// ```
+    /// let section = lisp_iter::LispIter::new(r#"((width 800) (height 600) (title "demo"))"#)
+    ///     .next()
+    ///     .unwrap();
+    /// let width = section.assoc("width").and_then(|v| v.as_integer());
+    /// let title = section.assoc("title").and_then(|v| v.as_quote());
+    /// assert_eq!((width, title), (Some(800), Some("demo")));
+    /// assert!(section.assoc("depth").is_none());
+    /// ```
+    pub fn assoc(&self, key: &str) -> Option<Atom<'a>> {
+        self.assoc_all(key).next()
+    }
+
+    /// Like [`Atom::assoc`], but yields the value of every matching entry in order.
+    pub fn assoc_all<'k>(&self, key: &'k str) -> AssocIter<'a, 'k> {
+        AssocIter {
+            iter: self.as_list(),
+            key,
+        }
+    }
+
+    /// Consumes a list of exactly `N` elements into an array, without allocating.
+    ///
+    /// Like [`AtomIter`], a non-list atom counts as a list of itself, so it converts into
+    /// a one-element array.
+    ///
+    /// ```
+    /// use lisp_iter::{ArityError, LispIter};
+    ///
+    /// let mut iter = LispIter::new("(1.0 2.0 3.0) (1.0 2.0)");
+    /// let [x, y, z] = iter.next().unwrap().try_into_array().unwrap();
+    /// assert!(x.eq_float(1.0) && y.eq_float(2.0) && z.eq_float(3.0));
+    /// let err = iter.next().unwrap().try_into_array::<3>().unwrap_err();
+    /// assert_eq!(err, ArityError::TooFew { got: 2 });
+    /// ```
+    pub fn try_into_array<const N: usize>(self) -> Result<[Atom<'a>; N], ArityError> {
+        let mut iter = self.into_iter();
+        let mut got = 0;
+        let array: [Option<Atom<'a>>; N] = core::array::from_fn(|_| {
+            let atom = iter.next();
+            got += atom.is_some() as usize;
+            atom
+        });
+        if got < N {
+            return Err(ArityError::TooFew { got });
+        }
+        if iter.next().is_some() {
+            return Err(ArityError::TooMany);
+        }
+        Ok(array.map(|atom| atom.expect("array was filled")))
+    }
+
+    /// Converts a list of exactly `N` numbers into an array, such as the coordinates of a
+    /// `(vertex 1.0 2.5 -3.0)`.
+    ///
+    /// Elements convert with `TryFrom`, so integers widen to floats but floats never
+    /// truncate to integers, and values out of range for `T` are errors. Like
+    /// [`Atom::try_into_array`], a non-list atom counts as a list of itself. The list isn't
+    /// consumed.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, ConvertError, LispIter, NumError};
+    ///
+    /// let (_, coords) = LispIter::new("(vertex 1.0 2.5 -3)").next().unwrap().split_head().unwrap();
+    /// let [x, y, z]: [f32; 3] = Atom::List(coords).parse_numbers().unwrap();
+    /// assert_eq!((x, y, z), (1.0, 2.5, -3.0));
+    ///
+    /// let rgb = LispIter::new("(255 128 300)").next().unwrap();
+    /// let err = rgb.parse_numbers::<u8, 3>().unwrap_err();
+    /// let error = ConvertError::OutOfRange { value: 300, target: "u8" };
+    /// assert_eq!(err, NumError::Element { index: 2, error });
+    /// ```
+    pub fn parse_numbers<T, const N: usize>(&self) -> Result<[T; N], NumError>
+    where
+        T: TryFrom<Atom<'a>, Error = ConvertError> + Copy + Default,
+    {
+        let mut out = [T::default(); N];
+        match self.parse_numbers_into(&mut out)? {
+            got if got < N => Err(NumError::Arity(ArityError::TooFew { got })),
+            _ => Ok(out),
+        }
+    }
+
+    /// Like [`Atom::parse_numbers`], but fills the start of `out` and returns how many
+    /// numbers were written. More numbers than fit in `out` are an error.
+    pub fn parse_numbers_into<T>(&self, out: &mut [T]) -> Result<usize, NumError>
+    where
+        T: TryFrom<Atom<'a>, Error = ConvertError>,
+    {
+        let mut iter = self.clone().into_iter();
+        for (index, slot) in out.iter_mut().enumerate() {
+            let Some(atom) = iter.next() else {
+                return Ok(index);
+            };
+            *slot = T::try_from(atom).map_err(|error| NumError::Element { index, error })?;
+        }
+        match iter.next() {
+            Some(_) => Err(NumError::Arity(ArityError::TooMany)),
+            None => Ok(out.len()),
+        }
+    }
+
+    /// Counts this atom and every atom below it, lists, vectors and maps included.
+    ///
+    /// A leaf counts 1, and so does a container, on top of its contents: `(a (b))` counts
+    /// 4. The count comes from a scan over the source that tracks no nesting and builds
+    /// no atoms, so it's cheap to take before doing real work on untrusted input. See
+    /// [`Atom::deep_count_capped`] to stop early.
+    ///
+    /// ```
+    /// let atom = lisp_iter::LispIter::new("(a (b [c] {:k 1}) ; (x)\n \"s\")").next().unwrap();
+    /// assert_eq!(atom.deep_count(), 10);
+    /// ```
+    pub fn deep_count(&self) -> usize {
+        self.deep_count_capped(usize::MAX).unwrap_or(usize::MAX)
+    }
+
+    /// Like [`Atom::deep_count`], but gives up with [`TooBig`] as soon as the count
+    /// exceeds `max`.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, TooBig};
+    ///
+    /// let atom = LispIter::new("(a (b c) d)").next().unwrap();
+    /// assert_eq!(atom.deep_count_capped(6), Ok(6));
+    /// assert_eq!(atom.deep_count_capped(5), Err(TooBig { max: 5 }));
+    /// ```
+    pub fn deep_count_capped(&self, max: usize) -> Result<usize, TooBig> {
+        let mut count = 1;
+        if let Self::List(iter) | Self::Vector(iter) | Self::Map(iter) = self {
+            let mut iter = iter.clone();
+            while let Some((token, span)) = iter.lex() {
+                count += match token {
+                    Token::Open(_) | Token::Quote { .. } | Token::Word => 1,
+                    Token::Apostrophe { head: true } => 1,
+                    // The quote form and its `quote`.
+                    Token::Apostrophe { head: false } => 2,
+                    Token::Bytes { closed } => iter.valid_bytes(closed, &span) as usize,
+                    Token::Close(_) | Token::LineComment | Token::BlockComment { .. } => 0,
+                };
+                if count > max {
+                    break;
+                }
+            }
+        }
+        match count > max {
+            true => Err(TooBig { max }),
+            false => Ok(count),
+        }
+    }
+
+    /// Counts the immediate children of a [`Atom::List`], or returns 1 for any other atom.
+    ///
+    /// The result always equals `self.clone().into_iter().count()`, matching how
+    /// [`AtomIter`] treats a non-list as a list of itself. The list isn't consumed.
+    ///
+    /// Children are skipped structurally: nested lists are bracket-matched rather than
+    /// parsed, and numbers aren't converted. The cost is a single scan of the list's
+    /// source text, so calling this before iterating the list roughly doubles the work on
+    /// it, but no more.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut atoms = LispIter::new(r#"(a (b c) "d)" ; e
+    ///     [f]) () x [y z]"#);
+    /// let list = atoms.next().unwrap();
+    /// assert_eq!(list.count_items(), 4);
+    /// assert_eq!(list.count_items(), list.clone().into_iter().count());
+    /// assert_eq!(atoms.next().unwrap().count_items(), 0);
+    /// assert_eq!(atoms.next().unwrap().count_items(), 1);
+    /// assert_eq!(atoms.next().unwrap().count_items(), 1);
+    /// ```
+    pub fn count_items(&self) -> usize {
+        match self {
+            Self::List(iter) => iter.clone().count_extents(),
+            _ => 1,
+        }
+    }
+
+    /// Counts the elements of a [`Atom::List`], or returns `None` for any other atom.
+    ///
+    /// Works on a clone of the inner iterator, so the list itself isn't consumed. This is
+    /// O(n) in the list's length: only its top-level elements are counted, but each of them
+    /// is still parsed.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let list = LispIter::new("(a (b c) ; note\n [d])").next().unwrap();
+    /// assert_eq!(list.list_len(), Some(3));
+    /// assert_eq!(list.list_len(), Some(3));
+    /// assert_eq!(LispIter::new("()").next().unwrap().list_len(), Some(0));
+    /// assert_eq!(LispIter::new("[a b]").next().unwrap().list_len(), None);
+    /// assert_eq!(LispIter::new("a").next().unwrap().list_len(), None);
+    /// ```
+    pub fn list_len(&self) -> Option<usize> {
+        match self {
+            Self::List(iter) => Some(iter.clone().count()),
+            _ => None,
+        }
+    }
+}
+
+/// Iterator over the key-value pairs of a map, see [`Atom::as_map_iter`].
+#[derive(Clone)]
+pub struct MapIter<'a> {
+    iter: LispIter<'a>,
+}
+
+impl<'a> Iterator for MapIter<'a> {
+    type Item = (Atom<'a>, Atom<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some((self.iter.next()?, self.iter.next()?))
+    }
+}
+
+/// Iterator over the values of an association list, see [`Atom::assoc_all`].
+#[derive(Clone)]
+pub struct AssocIter<'a, 'k> {
+    iter: Option<LispIter<'a>>,
+    key: &'k str,
+}
+
+impl<'a> Iterator for AssocIter<'a, '_> {
+    type Item = Atom<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for entry in self.iter.as_mut()? {
+            let Atom::List(mut entry) = entry else {
+                continue;
+            };
+            let matches = match entry.next_ranged() {
+                Some((Atom::Identifier(head), ..)) => head == self.key,
+                Some((Atom::Quote(head), _, range)) => head == self.key && entry.is_keyword(&range),
+                _ => false,
+            };
+            if !matches {
+                continue;
+            }
+            if let Some(value) = entry.next() {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the atoms at a path of nested lists, see [`LispIter::get_all`].
+#[derive(Clone)]
+pub struct PathIter<'s, 'p> {
+    root: LispIter<'s>,
+    path: &'p [&'p str],
+    /// Offset a match has to start at to be new.
+    from: usize,
+}
+
+impl<'s> Iterator for PathIter<'s, '_> {
+    type Item = Atom<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (atom, span) = find_path(self.root.clone(), self.path, self.from)?;
+        self.from = span.outer.start + 1;
+        Some(atom)
+    }
+}
+
+/// Searches depth-first for the first atom at `path` starting at or after `from`.
+fn find_path<'s>(mut iter: LispIter<'s>, path: &[&str], from: usize) -> Option<(Atom<'s>, Span)> {
+    let (segment, rest) = path.split_first()?;
+    while let Some((entry, span)) = iter.next_spanned() {
+        let Atom::List(mut entry) = entry else {
+            continue;
+        };
+        if span.outer.end <= from || entry.head_identifier() != Some(*segment) {
+            continue;
+        }
+        let found = match rest {
+            [] => entry
+                .next_spanned()
+                .filter(|(_, span)| span.outer.start >= from),
+            rest => find_path(entry, rest, from),
+        };
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+/// Helper iterator convenient for iterating over a [`Atom::List`]'s contence.
+///
+/// Can be constructed by calling `.into_iterator()` on any [`Atom`]
+///
+/// Only an [`Atom::List`] is iterated over. Every other atom, vectors and maps included,
+/// is yielded once as itself, as if it were the only element of a list. Use
+/// [`AtomIter::is_list`] to tell the two apart, or [`Atom::try_into_iter`] where only a
+/// list will do.
+///
+/// ```
+/// use lisp_iter::LispIter;
+///
+/// let mut atoms = LispIter::new("(a b) a [a b] {a b} \"a\" 1");
+/// let list = atoms.next().unwrap().into_iter();
+/// assert!(list.is_list());
+/// assert_eq!(list.count(), 2);
+/// for atom in atoms {
+///     let kind = atom.kind();
+///     let mut single = atom.into_iter();
+///     assert!(!single.is_list());
+///     assert_eq!(single.next().unwrap().kind(), kind);
+///     assert!(single.next().is_none());
+/// }
+/// ```
+pub struct AtomIter<'a> {
+    atom: Option<Atom<'a>>,
+}
+
+impl<'a> AtomIter<'a> {
+    pub fn new(atom: Atom<'a>) -> Self {
+        Self { atom: Some(atom) }
+    }
+}
+
+impl<'a> AtomIter<'a> {
+    /// Returns `true` if this iterates over the elements of an [`Atom::List`], rather than
+    /// yielding a single atom.
+    pub fn is_list(&self) -> bool {
+        matches!(self.atom, Some(Atom::List(_)))
+    }
+
+    /// Like [`Iterator::next`], but also returns where the atom came from.
+    ///
+    /// Elements of a list come with their span, as given by [`LispIter::next_spanned`].
+    /// A non-list atom carries no position, so it's yielded with `None`.
+    ///
+    /// ```
+    /// use lisp_iter::{Atom, LispIter};
+    ///
+    /// let mut atoms = LispIter::new("(x yz) 5");
+    /// let mut list = atoms.next().unwrap().into_iter();
+    /// assert_eq!(list.next_spanned().unwrap().1.unwrap().outer, 1..2);
+    /// assert_eq!(list.next_spanned().unwrap().1.unwrap().outer, 3..5);
+    /// let mut single = atoms.next().unwrap().into_iter();
+    /// let (five, span) = single.next_spanned().unwrap();
+    /// assert!(matches!(five, Atom::Integer(5)) && span.is_none());
+    /// ```
+    pub fn next_spanned(&mut self) -> Option<(Atom<'a>, Option<Span>)> {
+        match &mut self.atom {
+            Some(Atom::List(iter)) => iter.next_spanned().map(|(atom, span)| (atom, Some(span))),
+            atom => atom.take().map(|atom| (atom, None)),
+        }
+    }
+}
+
+impl<'a> Iterator for AtomIter<'a> {
+    type Item = Atom<'a>;
+
+    /// Returns either the contence of [`Atom::List`] or itself if it is not an [`Atom::List`]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.atom {
+            Some(Atom::List(iter)) => iter.next(),
+            atom => atom.take(),
+        }
+    }
+}
+
+/// Fused like [`LispIter`]: a single atom is only yielded once.
+impl core::iter::FusedIterator for AtomIter<'_> {}
+
+impl<'a> IntoIterator for Atom<'a> {
+    type Item = Atom<'a>;
+    type IntoIter = AtomIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        AtomIter::new(self)
+    }
+}
+
+/// Lists, vectors and maps are formatted like slices and maps of their elements.
+///
+/// Formatting recurses into nested containers, so beyond a depth of [`DEBUG_DEPTH_LIMIT`]
+/// their contents are elided as `...` rather than risking a stack overflow on input like
+/// `((((...`.
+///
+/// ```
+/// let deep = format!("{}{}", "(".repeat(100_000), ")".repeat(100_000));
+/// let atom = lisp_iter::LispIter::new(&deep).next().unwrap();
+/// assert!(format!("{:?}", atom).contains("[...]"));
+/// ```
+impl Debug for Atom<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        DebugAtom(self.clone(), 0).fmt(f)
+    }
+}
+
+/// Nesting depth beyond which [`Atom`]'s `Debug` output elides a container's contents.
+pub const DEBUG_DEPTH_LIMIT: usize = 64;
+
+/// An atom formatted at some nesting depth, for [`Atom`]'s `Debug`.
+struct DebugAtom<'a>(Atom<'a>, usize);
+
+impl Debug for DebugAtom<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let DebugAtom(atom, depth) = self;
+        let nested = |atom| DebugAtom(atom, depth + 1);
+        match atom {
+            Atom::Identifier(arg0) => f.debug_tuple("Identifier").field(arg0).finish(),
+            Atom::Quote(arg0) => f.debug_tuple("Quote").field(arg0).finish(),
+            Atom::Integer(arg0) => f.debug_tuple("Integer").field(arg0).finish(),
+            Atom::Float(arg0) => f.debug_tuple("Float").field(arg0).finish(),
+            Atom::Float32(arg0) => f.debug_tuple("Float32").field(arg0).finish(),
+            Atom::Rational(n, d) => f.debug_tuple("Rational").field(n).field(d).finish(),
+            Atom::Bytes(hex) => write!(f, "Bytes(\"{}\")", hex.escape_ascii()),
+            Atom::List(_) | Atom::Vector(_) if *depth >= DEBUG_DEPTH_LIMIT => {
+                f.debug_list().entry(&format_args!("...")).finish()
+            }
+            Atom::Map(_) if *depth >= DEBUG_DEPTH_LIMIT => {
+                f.debug_set().entry(&format_args!("...")).finish()
+            }
+            Atom::List(arg0) | Atom::Vector(arg0) => {
+                f.debug_list().entries(arg0.clone().map(nested)).finish()
+            }
+            Atom::Map(_) => {
+                let pairs = atom.as_map_iter().unwrap();
+                f.debug_map()
+                    .entries(pairs.map(|(key, value)| (nested(key), nested(value))))
+                    .finish()
+            }
+        }
+    }
+}
+
+/// Structural equality: identifiers and quotes compare by text, numbers by value, and
+/// lists, vectors and maps element by element.
+///
+/// An [`Atom::Integer`] never equals an [`Atom::Float`], even of the same value, and as
+/// with `f64` a NaN float equals nothing. Containers are compared by walking both trees in
+/// lockstep on clones of their iterators, so nothing is consumed and deep nesting needs no
+/// stack; only quote forms (see [`ParseConfig::quote_forms`]) are compared recursively.
+///
+/// ```
+/// use lisp_iter::{Atom, LispIter};
+///
+/// let parse = |src| LispIter::new(src).next().unwrap();
+/// assert_eq!(parse("(a \"b\" [1 2.5])"), parse("( a  \"b\" ; comment\n [1 2.5])"));
+/// assert_ne!(Atom::Integer(1), Atom::Float(1.0));
+/// assert_ne!(parse("(1)"), parse("(1.0)"));
+/// assert_ne!(Atom::Float(f64::NAN), Atom::Float(f64::NAN));
+/// assert_ne!(Atom::Identifier("a"), Atom::Quote("a"));
+///
+/// assert_ne!(parse("(a b)"), parse("(a b c)"));
+/// assert_ne!(parse("(a b c)"), parse("(a b)"));
+/// assert_ne!(parse("((a) b)"), parse("((a b))"));
+/// assert_ne!(parse("(a b)"), parse("[a b]"));
+/// assert_eq!(parse("()"), parse("( )"));
+/// ```
+impl PartialEq for Atom<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        let (Self::List(a) | Self::Vector(a) | Self::Map(a)) = self else {
+            return self.shallow_eq(other);
+        };
+        let (Self::List(b) | Self::Vector(b) | Self::Map(b)) = other else {
+            return false;
+        };
+        if self.kind() != other.kind() {
+            return false;
+        }
+        let (mut a, mut b) = (Walker::new(a.clone()), Walker::new(b.clone()));
+        loop {
+            match (a.next_event(), b.next_event()) {
+                (None, None) => return true,
+                (
+                    Some(Event::Enter(x) | Event::Leaf(x)),
+                    Some(Event::Enter(y) | Event::Leaf(y)),
+                ) if x.is_quote_form() || y.is_quote_form() => {
+                    if x != y {
+                        return false;
+                    }
+                    a.leave(&x);
+                    b.leave(&y);
+                }
+                (Some(Event::Enter(x)), Some(Event::Enter(y))) if x.kind() == y.kind() => {}
+                (Some(Event::Leaf(x)), Some(Event::Leaf(y))) if x.shallow_eq(&y) => {}
+                (Some(Event::Exit), Some(Event::Exit)) => {}
+                _ => return false,
+            }
+        }
+    }
+}
+
+/// Structural hash, consistent with the `PartialEq` impl: atoms that compare equal hash
+/// equally.
+///
+/// Identifiers, quotes and bytes hash their text, numbers their value (floats by bit
+/// pattern, with `-0.0` hashed as `0.0`), and containers their kind followed by their
+/// elements in order and an end marker. Like equality, containers are walked on clones
+/// of their iterators, recursing only into quote forms.
+///
+/// `Atom` isn't `Eq`, since a NaN float is unequal to itself; such an atom can be hashed
+/// but never found again under equality. Wrap atoms in an [`AtomKey`] to use them as map
+/// keys.
+///
+/// ```
+/// use core::hash::{BuildHasher, Hash};
+/// use lisp_iter::LispIter;
+/// use std::collections::hash_map::RandomState;
+///
+/// let state = RandomState::new();
+/// let hash = |input| state.hash_one(LispIter::new(input).next().unwrap());
+/// assert_eq!(hash("(a  \"b\" (1 -0.0))"), hash("(a \"b\" ; c\n (1 0.0))"));
+/// assert_ne!(hash("(a (b))"), hash("(a b)"));
+/// ```
+impl core::hash::Hash for Atom<'_> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        let (Self::List(iter) | Self::Vector(iter) | Self::Map(iter)) = self else {
+            return self.shallow_hash(state);
+        };
+        self.kind().hash(state);
+        let mut walker = Walker::new(iter.clone());
+        while let Some(event) = walker.next_event() {
+            match event {
+                Event::Enter(atom) => atom.kind().hash(state),
+                Event::Leaf(atom) => atom.shallow_hash(state),
+                Event::Exit => state.write_u8(0xff),
+            }
+        }
+        state.write_u8(0xff);
+    }
+}
+
+/// An [`Atom`] that is `Eq` and `Ord`, for use as a key in a `HashMap`, a `hashbrown`
+/// map or a `BTreeMap`, or for sorting into a canonical order.
+///
+/// Keys compare like their atoms, except that floats compare by bit pattern, as with
+/// [`f64::total_cmp`]: a NaN equals itself, so it can be found again, and `-0.0` no
+/// longer equals `0.0`. They hash like their atoms, and are ordered by
+/// [`Atom::total_cmp`].
+///
+/// ```
+/// use lisp_iter::{Atom, AtomKey, LispIter};
+/// use std::collections::HashMap;
+///
+/// let mut counts = HashMap::new();
+/// for atom in LispIter::new("(a 1) 0.5 b (a  1) 0.5") {
+///     *counts.entry(AtomKey(atom)).or_insert(0) += 1;
+/// }
+/// let key = |input| AtomKey(LispIter::new(input).next().unwrap());
+/// assert_eq!(counts[&key("(a 1)")], 2);
+/// assert_eq!(counts[&key("0.5")], 2);
+/// assert_eq!(counts.get(&key("c")), None);
+///
+/// counts.insert(AtomKey(Atom::Float(f64::NAN)), 1);
+/// assert_eq!(counts[&AtomKey(Atom::Float(f64::NAN))], 1);
+///
+/// let mut keys: Vec<_> = LispIter::new("b 0.0 2 (a) -0.0 a").map(AtomKey).collect();
+/// keys.sort();
+/// let sorted: Vec<_> = keys.iter().map(|key| format!("{:?}", key.0)).collect();
+/// assert_eq!(sorted, [
+///     "Integer(2)", "Float(-0.0)", "Float(0.0)", "Identifier(\"a\")", "Identifier(\"b\")",
+///     "[Identifier(\"a\")]",
+/// ]);
+/// ```
+#[repr(transparent)]
+#[derive(Clone, Debug)]
+pub struct AtomKey<'a>(pub Atom<'a>);
+
+impl PartialEq for AtomKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0) == Ordering::Equal
+    }
+}
+
+impl Eq for AtomKey<'_> {}
+
+impl core::hash::Hash for AtomKey<'_> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialOrd for AtomKey<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AtomKey<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// Structural ordering, consistent with the `PartialEq` impl.
+///
+/// Atoms of different kinds are ordered by kind alone, in this order: integer, rational,
+/// 32-bit float, float, identifier, quote, bytes, list, vector, map. So `(2)` sorts
+/// before `(1.0)`: an [`Atom::Integer`] is less than any [`Atom::Float`] whatever their
+/// values, just as they are never equal.
+///
+/// Atoms of the same kind compare by value: numbers numerically (rationals with equal
+/// values by denominator), identifiers, quotes and bytes by their text, and containers
+/// element by element, a prefix sorting first. Floats compare as `f64` does, so a NaN
+/// makes the atoms unordered; [`Atom::total_cmp`] orders those as well.
+///
+/// ```
+/// use lisp_iter::LispIter;
+///
+/// let atoms: Vec<_> = LispIter::new("(1 b) (1 a c) (1 a) (0.5 a) 2").collect();
+/// assert!(atoms[4] < atoms[3] && atoms[3] > atoms[0]);
+/// assert!(atoms[2] < atoms[1] && atoms[1] < atoms[0]);
+/// ```
+impl PartialOrd for Atom<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.compare(other, &|a, b| a.shallow_partial_cmp(b))
+    }
+}
+
+impl Atom<'_> {
+    /// Orders atoms like `partial_cmp`, except that floats compare with
+    /// [`f64::total_cmp`], so the order is total: NaNs sort past the infinities by sign,
+    /// and `-0.0` before `0.0`.
+    ///
+    /// For sorting into a deterministic, canonical order. `Atom` isn't `Ord`, since this
+    /// order disagrees with `==` on NaNs and signed zeros, as it does for `f64`; an
+    /// [`AtomKey`] is `Ord` by it.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut atoms: Vec<_> = LispIter::new("b -inf 3 a 0.0 -0.0 (x)").collect();
+    /// atoms.sort_by(|a, b| a.total_cmp(b));
+    /// let sorted: Vec<_> = atoms.iter().map(|a| format!("{:?}", a)).collect();
+    /// assert_eq!(sorted, [
+    ///     "Integer(3)", "Float(-inf)", "Float(-0.0)", "Float(0.0)", "Identifier(\"a\")",
+    ///     "Identifier(\"b\")", "[Identifier(\"x\")]",
+    /// ]);
+    /// ```
+    pub fn total_cmp(&self, other: &Atom<'_>) -> Ordering {
+        self.compare(other, &|a, b| Some(a.shallow_total_cmp(b)))
+            .expect("leaves are totally ordered")
+    }
+
+    /// Orders atoms lexicographically by walking both trees in lockstep, with `leaf` for
+    /// atoms of the same kind that aren't lists.
+    fn compare(
+        &self,
+        other: &Atom<'_>,
+        leaf: &impl Fn(&Atom<'_>, &Atom<'_>) -> Option<Ordering>,
+    ) -> Option<Ordering> {
+        let (Self::List(a) | Self::Vector(a) | Self::Map(a)) = self else {
+            return match self.rank().cmp(&other.rank()) {
+                Ordering::Equal => leaf(self, other),
+                order => Some(order),
+            };
+        };
+        let (Atom::List(b) | Atom::Vector(b) | Atom::Map(b)) = other else {
+            return Some(self.rank().cmp(&other.rank()));
+        };
+        if self.kind() != other.kind() {
+            return Some(self.rank().cmp(&other.rank()));
+        }
+        let (mut a, mut b) = (Walker::new(a.clone()), Walker::new(b.clone()));
+        loop {
+            let order = match (a.next_event(), b.next_event()) {
+                (None, None) => return Some(Ordering::Equal),
+                (None | Some(Event::Exit), None | Some(Event::Exit)) => Ordering::Equal,
+                (None | Some(Event::Exit), _) => Ordering::Less,
+                (_, None | Some(Event::Exit)) => Ordering::Greater,
+                (
+                    Some(Event::Enter(x) | Event::Leaf(x)),
+                    Some(Event::Enter(y) | Event::Leaf(y)),
+                ) if x.is_quote_form() || y.is_quote_form() => {
+                    let order = x.compare(&y, leaf)?;
+                    a.leave(&x);
+                    b.leave(&y);
+                    order
+                }
+                (Some(Event::Enter(x)), Some(Event::Enter(y))) => x.rank().cmp(&y.rank()),
+                (
+                    Some(Event::Enter(x) | Event::Leaf(x)),
+                    Some(Event::Enter(y) | Event::Leaf(y)),
+                ) => match x.rank().cmp(&y.rank()) {
+                    Ordering::Equal => leaf(&x, &y)?,
+                    order => order,
+                },
+            };
+            if order != Ordering::Equal {
+                return Some(order);
+            }
+        }
+    }
+
+    /// Position of the atom's kind in the order across kinds.
+    fn rank(&self) -> u8 {
+        match self.kind() {
+            AtomKind::Integer => 0,
+            AtomKind::Rational => 1,
+            AtomKind::Float32 => 2,
+            AtomKind::Float => 3,
+            AtomKind::Identifier => 4,
+            AtomKind::Quote => 5,
+            AtomKind::Bytes => 6,
+            AtomKind::List => 7,
+            AtomKind::Vector => 8,
+            AtomKind::Map => 9,
+        }
+    }
+
+    /// Orders two atoms of the same kind that aren't lists.
+    fn shallow_partial_cmp(&self, other: &Atom<'_>) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Float(a), Atom::Float(b)) => a.partial_cmp(b),
+            (Self::Float32(a), Atom::Float32(b)) => a.partial_cmp(b),
+            _ => Some(self.shallow_total_cmp(other)),
+        }
+    }
+
+    /// Orders two atoms of the same kind that aren't lists, floats by `total_cmp`.
+    fn shallow_total_cmp(&self, other: &Atom<'_>) -> Ordering {
+        match (self, other) {
+            (Self::Identifier(a), Atom::Identifier(b)) | (Self::Quote(a), Atom::Quote(b)) => {
+                a.cmp(b)
+            }
+            (Self::Integer(a), Atom::Integer(b)) => a.cmp(b),
+            (Self::Float(a), Atom::Float(b)) => a.total_cmp(b),
+            (Self::Float32(a), Atom::Float32(b)) => a.total_cmp(b),
+            (Self::Rational(a, b), Atom::Rational(c, d)) => {
+                let (a, b, c, d) = (*a as i128, *b as i128, *c as i128, *d as i128);
+                (a * d).cmp(&(c * b)).then(b.cmp(&d))
+            }
+            (Self::Bytes(a), Atom::Bytes(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        }
+    }
+
+    /// Hashes an atom that isn't a list, consistently with [`Atom::shallow_eq`], or a
+    /// quote form in full.
+    fn shallow_hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        use core::hash::Hash;
+
+        if self.is_quote_form() {
+            return self.hash(state);
+        }
+
+        self.kind().hash(state);
+        match *self {
+            Self::Identifier(text) | Self::Quote(text) => text.hash(state),
+            Self::Bytes(hex) => hex.hash(state),
+            Self::Integer(n) => n.hash(state),
+            Self::Float(x) => (x + 0.0).to_bits().hash(state),
+            Self::Float32(x) => (x + 0.0).to_bits().hash(state),
+            Self::Rational(n, d) => (n, d).hash(state),
+            Self::List(_) | Self::Vector(_) | Self::Map(_) => {}
+        }
+    }
+
+    /// Whether this is a list read from the `'datum` shorthand, see
+    /// [`ParseConfig::quote_forms`].
+    fn is_quote_form(&self) -> bool {
+        matches!(self, Atom::List(iter) if iter.quoted)
+    }
+
+    /// Whether `other` is this very atom: a list, vector or map parsed from the same text,
+    /// or another atom of the same value, floats compared bit for bit.
+    fn is_same(&self, other: &Atom<'_>) -> bool {
+        match (self, other) {
+            (Self::List(a), Atom::List(b))
+            | (Self::Vector(a), Atom::Vector(b))
+            | (Self::Map(a), Atom::Map(b)) => {
+                core::ptr::eq(a.input, b.input)
+                    && core::ptr::eq(a.prefix, b.prefix)
+                    && core::ptr::eq(a.suffix, b.suffix)
+                    && a.base == b.base
+                    && a.quoted == b.quoted
+            }
+            (Self::Float(a), Atom::Float(b)) => a.to_bits() == b.to_bits(),
+            (Self::Float32(a), Atom::Float32(b)) => a.to_bits() == b.to_bits(),
+            _ => self.shallow_eq(other),
+        }
+    }
+
+    /// Compares two atoms without looking into lists, which are always unequal.
+    fn shallow_eq(&self, other: &Atom<'_>) -> bool {
+        match (self, other) {
+            (Self::Identifier(a), Atom::Identifier(b)) | (Self::Quote(a), Atom::Quote(b)) => a == b,
+            (Self::Integer(a), Atom::Integer(b)) => a == b,
+            (Self::Float(a), Atom::Float(b)) => a == b,
+            (Self::Float32(a), Atom::Float32(b)) => a == b,
+            (Self::Rational(a, b), Atom::Rational(c, d)) => (a, b) == (c, d),
+            (Self::Bytes(a), Atom::Bytes(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Error converting an [`Atom`] into a Rust value with `TryFrom` or a checked accessor
+/// like [`Atom::as_u16`].
+///
+/// Together with the [`ParseError`]s of the `LispIter::expect_*` helpers it converts into
+/// a [`DecodeError`], so one `?` chain covers presence, type and range:
+///
+/// ```
+/// use lisp_iter::{DecodeError, LispIter};
+///
+/// fn port(input: &str) -> Result<u16, DecodeError<'_>> {
+///     let mut iter = LispIter::new(input);
+///     iter.expect_identifier()?;
+///     Ok(iter.expect_next()?.as_u16()?)
+/// }
+///
+/// assert_eq!(port("port 8080.0"), Ok(8080));
+/// assert_eq!(port("port 70000").unwrap_err().to_string(), "value 70000 out of range for u16");
+/// assert!(port("port").is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The atom's kind doesn't convert to the target type at all.
+    WrongKind {
+        found: AtomKind,
+        target: &'static str,
+    },
+    /// The integer doesn't fit in the target type.
+    OutOfRange { value: i64, target: &'static str },
+}
+
+impl core::fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::WrongKind { found, target } => {
+                write!(f, "cannot convert {} to {}", found, target)
+            }
+            Self::OutOfRange { value, target } => {
+                write!(f, "value {} out of range for {}", value, target)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ConvertError {}
+
+/// Error returned by [`Atom::deep_count_capped`]: the atom holds more than `max` atoms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TooBig {
+    pub max: usize,
+}
+
+impl core::fmt::Display for TooBig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "more than {} atoms", self.max)
+    }
+}
+
+impl core::error::Error for TooBig {}
+
+/// Error returned by [`Atom::try_into_iter`]: the atom isn't a list.
+#[derive(Clone, Debug)]
+pub struct NotAList<'a> {
+    /// The atom that was found instead.
+    pub atom: Atom<'a>,
+}
+
+impl core::fmt::Display for NotAList<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "expected list, found {}", self.atom.kind())
+    }
+}
+
+impl core::error::Error for NotAList<'_> {}
+
+/// Error returned by [`LispIter::collect_into`]: the atoms don't fit in the buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overflow {
+    /// Number of atoms written, i.e. the length of the buffer.
+    pub written: usize,
+}
+
+impl core::fmt::Display for Overflow {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "more than {} atoms", self.written)
+    }
+}
+
+impl core::error::Error for Overflow {}
+
+/// Error returned by [`Atom::try_into_array`]: the list has the wrong number of elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArityError {
+    /// The list ended after `got` elements.
+    TooFew { got: usize },
+    /// Elements remain after the array was filled.
+    TooMany,
+}
+
+impl core::fmt::Display for ArityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooFew { got } => write!(f, "too few elements, got {}", got),
+            Self::TooMany => f.write_str("too many elements"),
+        }
+    }
+}
+
+impl core::error::Error for ArityError {}
+
+/// Error returned by [`Atom::parse_numbers`] and [`Atom::parse_numbers_into`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NumError {
+    /// The list has the wrong number of elements.
+    Arity(ArityError),
+    /// The element at `index` isn't a number of the right type, or is out of range.
+    Element { index: usize, error: ConvertError },
+}
+
+impl core::fmt::Display for NumError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Arity(error) => write!(f, "{}", error),
+            Self::Element { index, error } => write!(f, "element {}: {}", index, error),
+        }
+    }
+}
+
+impl core::error::Error for NumError {}
+
+/// Error returned by [`LispIter::unget`]: the atom isn't the one just returned. It's
+/// handed back as `atom`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UngetError<'a> {
+    pub atom: Atom<'a>,
+}
+
+impl core::fmt::Display for UngetError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "can't unget {}, it wasn't just returned",
+            self.atom.kind()
+        )
+    }
+}
+
+impl core::error::Error for UngetError<'_> {}
+
+macro_rules! impl_try_from_integer {
+    ($($t:ty),*) => {$(
+        /// Converts an [`Atom::Integer`], failing rather than truncating if it doesn't fit.
+        impl TryFrom<Atom<'_>> for $t {
+            type Error = ConvertError;
+
+            fn try_from(atom: Atom<'_>) -> Result<Self, Self::Error> {
+                let target = stringify!($t);
+                match atom {
+                    Atom::Integer(value) => {
+                        <$t>::try_from(value).map_err(|_| ConvertError::OutOfRange { value, target })
+                    }
+                    atom => Err(ConvertError::WrongKind { found: atom.kind(), target }),
+                }
+            }
+        }
+    )*};
+}
+
+impl_try_from_integer!(i64, i32, u32, u16, u8);
+
+macro_rules! impl_checked_accessors {
+    ($($name:ident: $t:ty),*) => {
+        impl Atom<'_> {$(
+            #[doc = concat!("Returns the value of an [`Atom::Integer`] as a `", stringify!($t), "`, ")]
+            #[doc = "or a [`ConvertError`] if it doesn't fit or the atom isn't a number."]
+            ///
+            /// Unlike `TryFrom`, this also accepts an [`Atom::Float`] holding a whole number,
+            /// as with [`Atom::as_i64_lossy`], so `8080.0` reads as `8080`. Other floats are
+            /// [`ConvertError::WrongKind`]. Negative values for an unsigned type are
+            /// [`ConvertError::OutOfRange`], like any other value out of range.
+            pub fn $name(&self) -> Result<$t, ConvertError> {
+                let target = stringify!($t);
+                let value = self.as_i64_lossy().ok_or(ConvertError::WrongKind {
+                    found: self.kind(),
+                    target,
+                })?;
+                <$t>::try_from(value).map_err(|_| ConvertError::OutOfRange { value, target })
+            }
+        )*}
+    };
+}
+
+impl_checked_accessors!(as_u8: u8, as_u16: u16, as_u32: u32, as_i32: i32);
+
+/// Converts an [`Atom::Float`] or an [`Atom::Integer`], see [`Atom::as_f64`].
+impl TryFrom<Atom<'_>> for f64 {
+    type Error = ConvertError;
+
+    fn try_from(atom: Atom<'_>) -> Result<Self, Self::Error> {
+        atom.as_f64().ok_or(ConvertError::WrongKind {
+            found: atom.kind(),
+            target: "f64",
+        })
+    }
+}
+
+/// Converts an [`Atom::Float32`] as is, or an [`Atom::Float`] or an [`Atom::Integer`],
+/// rounding to the nearest `f32` as with `as`. Finite values beyond the range of `f32`
+/// become infinite.
+impl TryFrom<Atom<'_>> for f32 {
+    type Error = ConvertError;
+
+    fn try_from(atom: Atom<'_>) -> Result<Self, Self::Error> {
+        if let Atom::Float32(v) = atom {
+            return Ok(v);
+        }
+        let v = atom.as_f64().ok_or(ConvertError::WrongKind {
+            found: atom.kind(),
+            target: "f32",
+        })?;
+        Ok(v as f32)
+    }
+}
+
+/// Converts an [`Atom::Identifier`] or an [`Atom::Quote`] into its text.
+///
+/// Together with the numeric conversions this makes reading fields short:
+///
+/// ```
+/// use lisp_iter::{ConvertError, LispIter};
+///
+/// struct Server<'a> {
+///     host: &'a str,
+///     port: u16,
+///     timeout: f64,
+/// }
+///
+/// fn server(input: &str) -> Result<Server<'_>, ConvertError> {
+///     let mut args = LispIter::new(input);
+///     Ok(Server {
+///         host: args.next().unwrap().try_into()?,
+///         port: args.next().unwrap().try_into()?,
+///         timeout: args.next().unwrap().try_into()?,
+///     })
+/// }
+///
+/// let s = server(r#""example.com" 8080 2"#).unwrap();
+/// assert_eq!((s.host, s.port, s.timeout), ("example.com", 8080, 2.0));
+/// assert!(server(r#""example.com" 80800 2"#).is_err());
+/// ```
+impl<'a> TryFrom<Atom<'a>> for &'a str {
+    type Error = ConvertError;
+
+    fn try_from(atom: Atom<'a>) -> Result<Self, Self::Error> {
+        match atom {
+            Atom::Identifier(s) | Atom::Quote(s) => Ok(s),
+            atom => Err(ConvertError::WrongKind {
+                found: atom.kind(),
+                target: "&str",
+            }),
+        }
+    }
+}
+
+impl<'s> LispIter<'s> {
+    /// Lexes the next token, comments included, returning its class and byte range.
+    fn lex(&mut self) -> Option<(Token, Range<usize>)> {
+        let (start, c) = self.find_char(|c| !c.is_whitespace())?;
+        let (token, end) = match c {
+            '(' => (Token::Open(Bracket::Paren), start + 1),
+            ')' => (Token::Close(Bracket::Paren), start + 1),
+            '[' => (Token::Open(Bracket::Square), start + 1),
+            ']' => (Token::Close(Bracket::Square), start + 1),
+            '{' => (Token::Open(Bracket::Curly), start + 1),
+            '}' => (Token::Close(Bracket::Curly), start + 1),
+            ';' => {
+                let (end, _) = self.find_char(|c| c == '\n')?;
+                (Token::LineComment, end)
+            }
+            '#' if self.next_char_if('|') => {
+                let mut depth = 1;
+                let closed = loop {
+                    match self.next_char() {
+                        Some((_, '|')) if self.next_char_if('#') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break true;
+                            }
+                        }
+                        Some((_, '#')) if self.next_char_if('|') => depth += 1,
+                        Some(_) => {}
+                        None => break false, // unclosed block comment
+                    }
+                };
+                (Token::BlockComment { closed }, self.offset())
+            }
+            '/' if self.config.c_comments && self.next_char_if('*') => {
+                let closed = loop {
+                    match self.next_char() {
+                        Some((_, '*')) if self.next_char_if('/') => break true,
+                        Some(_) => {}
+                        None => break false, // unclosed block comment
+                    }
+                };
+                (Token::BlockComment { closed }, self.offset())
+            }
+            '\'' if self.quoted && start == 0 => (Token::Apostrophe { head: true }, start + 1),
+            '\'' if self.config.quote_forms && self.quotes_datum() => {
+                (Token::Apostrophe { head: false }, start + 1)
+            }
+            '#' if self.next_char_if('"') => match self.find_char(|c| c == '"') {
+                Some((end, _)) => (Token::Bytes { closed: true }, end + 1),
+                None => (Token::Bytes { closed: false }, self.end()), // unclosed quote
+            },
+            '"' => match self.find_char(|c| c == '"') {
+                Some((end, _)) => (
+                    Token::Quote {
+                        raw: None,
+                        closed: true,
+                    },
+                    end + 1,
+                ),
+                None => (
+                    Token::Quote {
+                        raw: None,
+                        closed: false,
+                    },
+                    self.end(),
+                ), // unclosed quote
+            },
+            'r' if self.raw_quote_opener() => {
+                let hashes = self.chars.byte - start - 'r'.len_utf8() - '"'.len_utf8();
+                let closed = loop {
+                    match self.next_char() {
+                        Some((_, '"')) if (0..hashes).all(|_| self.next_char_if('#')) => {
+                            break true
+                        }
+                        Some(_) => {}
+                        None => break false, // unclosed quote
+                    }
+                };
+                let raw = Some(hashes);
+                (Token::Quote { raw, closed }, self.offset())
+            }
+            _ => {
+                let mut end = self.end();
+                while let Some((i, c)) = self.peek_char() {
+                    if is_delimiter(c) {
+                        end = i;
+                        break;
+                    }
+                    self.next_char();
+                }
+                (Token::Word, end)
+            }
+        };
+        Some((token, start..end))
+    }
+
+    /// Whether the `'` just lexed is directly followed by a datum, rather than by
+    /// whitespace, a closer, a comment or the end of input.
+    fn quotes_datum(&self) -> bool {
+        let mut fork = self.clone();
+        match fork.next_char() {
+            Some((_, '(' | '[' | '{' | '"')) => true,
+            Some((_, '#')) => !fork.next_char_if('|'),
+            Some((_, '/')) if self.config.c_comments => !fork.next_char_if('*'),
+            Some((_, c)) => !is_delimiter(c),
+            None => false,
+        }
+    }
+
+    /// Consumes the datum of a quote form whose `'` was just lexed, returning the offset
+    /// where it ends and the error of an unclosed list or string.
+    fn quoted_datum(&mut self) -> (usize, Option<ParseError<'s>>) {
+        loop {
+            let (token, span) = self.lex().expect("a quote form's `'` precedes a datum");
+            let offset = self.pos(span.start);
+            return match token {
+                Token::Apostrophe { .. } => continue,
+                Token::Open(bracket) => match self.close_bracket() {
+                    Some((end, closer)) => {
+                        let error = self.mismatch(bracket, end, closer);
+                        (end + ')'.len_utf8(), error)
+                    }
+                    None => (self.end(), Some(ParseError::UnclosedList { offset })),
+                },
+                Token::Quote { closed: false, .. } | Token::Bytes { closed: false } => {
+                    (span.end, Some(ParseError::UnclosedQuote { offset }))
+                }
+                _ => (span.end, None),
+            };
+        }
+    }
+
+    /// Consumes the `#`s and `"` after an `r` if they open a raw string, returning whether
+    /// they did.
+    fn raw_quote_opener(&mut self) -> bool {
+        let mut fork = self.clone();
+        while fork.next_char_if('#') {}
+        let opens = fork.next_char_if('"');
+        if opens {
+            (self.chars, self.newline) = (fork.chars, fork.newline);
+        }
+        opens
+    }
+
+    /// Consumes the rest of a list or vector whose opening bracket was just lexed.
+    ///
+    /// Returns the offset of the matching closer, or `None` if the list is unclosed. All
+    /// kinds of brackets nest alike, so any closer closes the innermost open bracket.
+    fn close_list(&mut self) -> Option<usize> {
+        self.close_bracket().map(|(end, _)| end)
+    }
+
+    /// Like [`LispIter::close_list`], but also returns the kind of the closer, which may
+    /// not match the opening bracket.
+    fn close_bracket(&mut self) -> Option<(usize, Bracket)> {
+        let mut depth = 0usize;
+        while let Some((token, span)) = self.lex() {
+            match token {
+                Token::Open(_) => depth += 1,
+                Token::Close(closer) if depth == 0 => return Some((span.start, closer)),
+                Token::Close(_) => depth -= 1,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// The error for a closer at offset `end` into `input` closing `bracket`, if it's of
+    /// another kind.
+    fn mismatch(&self, bracket: Bracket, end: usize, closer: Bracket) -> Option<ParseError<'s>> {
+        (bracket != closer).then(|| ParseError::MismatchedCloser {
+            offset: self.pos(end),
+            expected: bracket.closer(),
+            found: closer.closer(),
+        })
+    }
+
+    /// Whether `text` is valid as the contents of a `#"..."` byte string.
+    fn is_hex(text: &str) -> bool {
+        text.len() % 2 == 0 && text.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+
+    /// Whether the [`Token::Bytes`] at `span` makes an [`Atom::Bytes`].
+    fn valid_bytes(&self, closed: bool, span: &Range<usize>) -> bool {
+        let end = match closed {
+            true => span.end - '"'.len_utf8(),
+            false => span.end,
+        };
+        Self::is_hex(self.text(span.start + "#\"".len()..end))
+    }
+
+    /// Whether `text` starts like a number, i.e. with a digit or a sign followed by a digit.
+    fn is_numeric(text: &str) -> bool {
+        matches!(
+            text.as_bytes(),
+            [b'0'..=b'9', ..] | [b'-' | b'+', b'0'..=b'9', ..]
+        )
+    }
+
+    /// Whether `text` starts like a number but doesn't parse as one.
+    fn is_invalid_number(text: &str) -> bool {
+        Self::is_numeric(text) && matches!(Self::word(text), Atom::Identifier(_))
+    }
+
+    /// Whether `text` is an integer, optionally signed, too large for `i64`.
+    fn is_integer_overflow(text: &str) -> bool {
+        let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+        !digits.is_empty()
+            && digits.bytes().all(|b| b.is_ascii_digit())
+            && text.parse::<i64>().is_err()
+    }
+
+    fn word<'a>(text: &'a str) -> Atom<'a> {
+        match text.as_bytes()[0] {
+            b':' => Atom::Quote(&text[':'.len_utf8()..]),
+            b'-' | b'+' | b'0'..=b'9' => {
+                if let Ok(v) = text.parse() {
+                    Atom::Integer(v)
+                } else if let Some(v) = text.strip_suffix("f32").and_then(|t| t.parse().ok()) {
+                    Atom::Float32(v)
+                } else if let Ok(v) = text.parse() {
+                    Atom::Float(v)
+                } else if let Some((n, d)) = Self::rational(text) {
+                    Atom::Rational(n, d)
+                } else {
+                    Atom::Identifier(text) // fallback
+                }
+            }
+            _ => Atom::Identifier(text),
+        }
+    }
+
+    /// Splits `n/d` into its numerator and non-zero denominator.
+    fn rational(text: &str) -> Option<(i64, u64)> {
+        let (n, d) = text.split_once('/')?;
+        let d = d.parse().ok().filter(|d| *d != 0)?;
+        Some((n.parse().ok()?, d))
+    }
+
+    /// Like `word`, but turns a float written with a non-negative exponent into an integer
+    /// if it's a whole number within `i64` range.
+    fn exponent_integer<'a>(text: &'a str) -> Atom<'a> {
+        let atom = Self::word(text);
+        let exponent = text.find(['e', 'E']).map(|i| &text[i + 1..]);
+        match (&atom, exponent) {
+            (Atom::Float(_), Some(exponent)) if !exponent.starts_with('-') => {
+                atom.as_i64_lossy().map_or(atom, Atom::Integer)
+            }
+            _ => atom,
+        }
+    }
+
+    /// Parses the next atom or reports the next error, whichever comes first.
+    fn advance(&mut self) -> Option<Parsed<'s>> {
+        let start = self.offset();
+        loop {
+            let (token, span) = self.lex()?;
+            if let Some(parsed) = self.parse(token, span, false) {
+                self.last = Some(start);
+                return Some(parsed);
+            }
+        }
+    }
+
+    /// Moves the iterator to an offset into the lexed text.
+    fn seek(&mut self, offset: usize) {
+        self.lex_from(offset);
+    }
+
+    /// Turns a lexed token into a step, or `None` for a comment that needs no reporting.
+    ///
+    /// With `enter` set, a list's contents aren't consumed: the list atom is still built
+    /// from a scan over a clone, but the iterator stays just after the `(`. Quote forms are
+    /// consumed either way.
+    fn parse(&mut self, token: Token, mut outer: Range<usize>, enter: bool) -> Option<Parsed<'s>> {
+        let offset = self.pos(outer.start);
+        let mut inner = outer.clone();
+        let (atom, error) = match token {
+            Token::LineComment | Token::BlockComment { closed: true } => return None,
+            Token::BlockComment { closed: false } => {
+                let error = ParseError::UnclosedBlockComment { offset };
+                (None, Some(error))
+            }
+            Token::Close(_) => (None, Some(ParseError::UnexpectedCloser { offset })),
+            Token::Open(bracket) => {
+                inner.start = outer.end;
+                let close = match enter {
+                    true => self.clone().close_bracket(),
+                    false => self.close_bracket(),
+                };
+                let error = match close {
+                    Some((end, closer)) => {
+                        inner.end = end;
+                        outer.end = end + ')'.len_utf8();
+                        let odd = bracket == Bracket::Curly
+                            && self.sublist(inner.clone()).count_extents() % 2 == 1;
+                        let odd = odd.then_some(ParseError::OddMapLength { offset });
+                        self.mismatch(bracket, end, closer).or(odd)
+                    }
+                    None => {
+                        inner.end = self.end();
+                        outer.end = self.end();
+                        Some(ParseError::UnclosedList { offset })
+                    }
+                };
+                let iter = self.sublist(inner.clone());
+                let atom = match bracket {
+                    Bracket::Paren => Atom::List(iter),
+                    Bracket::Square => Atom::Vector(iter),
+                    Bracket::Curly => Atom::Map(iter),
+                };
+                (Some(atom), error)
+            }
+            Token::Apostrophe { head: true } => (Some(Atom::Identifier("quote")), None),
+            Token::Apostrophe { head: false } => {
+                let (end, error) = self.quoted_datum();
+                outer.end = end;
+                inner = outer.clone();
+                let iter = LispIter {
+                    quoted: true,
+                    ..self.sublist(outer.clone())
+                };
+                (Some(Atom::List(iter)), error)
+            }
+            Token::Quote { raw, closed } => {
+                let hashes = raw.unwrap_or(0) * '#'.len_utf8();
+                inner.start += hashes + '"'.len_utf8();
+                if raw.is_some() {
+                    inner.start += 'r'.len_utf8();
+                }
+                let error = match closed {
+                    true => {
+                        inner.end -= hashes + '"'.len_utf8();
+                        None
+                    }
+                    false => Some(ParseError::UnclosedQuote { offset }),
+                };
+                (Some(Atom::Quote(self.text(inner.clone()))), error)
+            }
+            Token::Bytes { closed } => {
+                inner.start += "#\"".len();
+                if closed {
+                    inner.end -= '"'.len_utf8();
+                }
+                let text = self.text(inner.clone());
+                let atom = Self::is_hex(text).then_some(Atom::Bytes(text.as_bytes()));
+                let error = match (closed, &atom) {
+                    (false, _) => Some(ParseError::UnclosedQuote { offset }),
+                    (true, None) => Some(ParseError::InvalidBytes { offset }),
+                    (true, Some(_)) => None,
+                };
+                (atom, error)
+            }
+            Token::Word => {
+                let text = self.text(outer.clone());
+                let error = match self.config.strict_numbers {
+                    true if Self::is_invalid_number(text) => Some(ParseError::InvalidNumber {
+                        offset,
+                        token: text,
+                    }),
+                    true if Self::is_integer_overflow(text) => {
+                        Some(ParseError::IntegerOverflow { offset })
+                    }
+                    _ => None,
+                };
+                if text.starts_with(':') {
+                    inner.start += ':'.len_utf8();
+                }
+                let atom = match self.config.exponent_integers {
+                    true => Self::exponent_integer(text),
+                    false => Self::word(text),
+                };
+                (Some(atom), error)
+            }
+        };
+        let span = Span {
+            outer: self.pos(outer.start)..self.pos(outer.end),
+            inner: self.pos(inner.start)..self.pos(inner.end),
+        };
+        Some(Parsed {
+            atom,
+            error,
+            span,
+            range: outer,
+        })
+    }
+}
+
+/// Event of a [`Walker`].
+enum Event<'s> {
+    /// A list was entered. Its contents follow, then a matching `Exit`.
+    Enter(Atom<'s>),
+    Leaf(Atom<'s>),
+    Exit,
+}
+
+/// Walks the whole tree below a [`LispIter`] in source order without recursing.
+///
+/// Lists are entered in place rather than through their nested iterators, so the only
+/// state kept is the current depth. Unclosed lists are exited at the end of input.
+///
+/// Quote forms have no closer to exit them at, so they come as leaves. Comparisons
+/// recurse into them instead, using [`Walker::leave`] to catch up on the other side.
+#[derive(Clone)]
+struct Walker<'s> {
+    iter: LispIter<'s>,
+    depth: usize,
+}
+
+impl<'s> Walker<'s> {
+    fn new(iter: LispIter<'s>) -> Self {
+        Walker { iter, depth: 0 }
+    }
+
+    fn next_event(&mut self) -> Option<Event<'s>> {
+        loop {
+            let Some((token, span)) = self.iter.lex() else {
+                if self.depth == 0 {
+                    return None;
+                }
+                self.depth -= 1;
+                return Some(Event::Exit);
+            };
+            if matches!(token, Token::Close(_)) && self.depth > 0 {
+                self.depth -= 1;
+                return Some(Event::Exit);
+            }
+            match self.iter.parse(token, span, true) {
+                Some(Parsed {
+                    atom: Some(atom @ (Atom::List(_) | Atom::Vector(_) | Atom::Map(_))),
+                    ..
+                }) if !atom.is_quote_form() => {
+                    self.depth += 1;
+                    return Some(Event::Enter(atom));
+                }
+                Some(Parsed {
+                    atom: Some(atom), ..
+                }) => return Some(Event::Leaf(atom)),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Skips the rest of `atom`, just returned by `next_event`, if it was entered.
+    fn leave(&mut self, atom: &Atom<'_>) {
+        if matches!(atom, Atom::List(_) | Atom::Vector(_) | Atom::Map(_)) && !atom.is_quote_form() {
+            self.iter.close_list();
+            self.depth -= 1;
+        }
+    }
+}
+
+impl LispIter<'_> {
+    /// Counts the remaining expressions, skipping them with [`LispIter::next_extent`].
+    fn count_extents(mut self) -> usize {
+        let mut count = 0;
+        while self.next_extent().is_some() {
+            count += 1;
+        }
+        count
+    }
+
+    /// Skips over the next expression without building an atom, returning its outer range
+    /// within `input`. Comments and stray `)`s are skipped the same way as by `advance`.
+    fn next_extent(&mut self) -> Option<Range<usize>> {
+        loop {
+            let (token, span) = self.lex()?;
+            return Some(match token {
+                Token::LineComment | Token::BlockComment { .. } | Token::Close(_) => continue,
+                Token::Bytes { closed } if !self.valid_bytes(closed, &span) => continue,
+                Token::Open(_) => match self.close_list() {
+                    Some(end) => span.start..end + ')'.len_utf8(),
+                    None => span.start..self.end(),
+                },
+                Token::Apostrophe { head: false } => span.start..self.quoted_datum().0,
+                Token::Quote { .. } | Token::Bytes { .. } | Token::Word => span,
+                Token::Apostrophe { head: true } => span,
+            });
+        }
+    }
+}
+
+/// One step of [`LispIter::advance`]: an atom, an error, or an atom recovered from an error.
+struct Parsed<'s> {
+    atom: Option<Atom<'s>>,
+    error: Option<ParseError<'s>>,
+    /// Source of the atom, or of the error if there is no atom.
+    span: Span,
+    /// Like `span.outer`, but as a range of the lexed text.
+    range: Range<usize>,
+}
+
+/// Where an atom was parsed from, as byte ranges into the outermost input.
+///
+/// For a [`Atom::List`], `outer` covers both parens and `inner` only what's between
+/// them, so `()` has an empty `inner` sitting between the parens. Likewise for the
+/// brackets of an [`Atom::Vector`] or [`Atom::Map`], for an
+/// [`Atom::Quote`] with or without its quotes, or its `:` for a keyword. For other atoms
+/// both are the same. An unclosed list or quote has no closer, so both ranges end at the
+/// end of input.
+///
+/// ```
+/// use lisp_iter::{LispIter, Span};
+///
+/// let mut iter = LispIter::new("() [x] \"q\" :k 7 (open");
+/// let spans: Vec<_> = std::iter::from_fn(|| iter.next_spanned()).map(|(_, span)| span).collect();
+/// assert_eq!(
+///     spans,
+///     [
+///         Span { outer: 0..2, inner: 1..1 },
+///         Span { outer: 3..6, inner: 4..5 },
+///         Span { outer: 7..10, inner: 8..9 },
+///         Span { outer: 11..13, inner: 12..13 },
+///         Span { outer: 14..15, inner: 14..15 },
+///         Span { outer: 16..21, inner: 17..21 },
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub outer: Range<usize>,
+    pub inner: Range<usize>,
+}
+
+impl<'s> Iterator for LispIter<'s> {
+    type Item = Atom<'s>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(atom) = self.advance()?.atom {
+                return Some(atom);
+            }
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.nth_expr(n)
+    }
+
+    /// Every atom takes at least one byte, so the remaining input length bounds the count.
+    /// Counting exactly would mean scanning the rest of the input.
+    ///
+    /// ```
+    /// let mut iter = lisp_iter::LispIter::new("(a b) c");
+    /// assert_eq!(iter.size_hint(), (0, Some(7)));
+    /// iter.next();
+    /// assert_eq!(iter.size_hint(), (0, Some(2)));
+    /// iter.next();
+    /// assert_eq!(iter.size_hint(), (0, Some(0)));
+    /// ```
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end() - self.offset()))
+    }
+}
+
+/// Once the input runs out, the lexer has consumed every char, so each further call
+/// returns `None` too. That includes input ending in an unclosed list, string or comment,
+/// which are recovered from by running them to the end.
+///
+/// ```
+/// use lisp_iter::{LispIter, ParseConfig};
+///
+/// let config = ParseConfig { quote_forms: true, ..Default::default() };
+/// for input in ["", "a", "(a", "\"a", "#|a", "a ; b", "#\"0", ")", "'", "'(a"] {
+///     let mut iter = LispIter::with_config(input, config);
+///     while iter.next().is_some() {}
+///     assert!((0..3).all(|_| iter.next().is_none() && iter.try_next().is_none()));
+///
+///     let mut atom = lisp_iter::Atom::List(LispIter::new(input)).into_iter();
+///     while atom.next().is_some() {}
+///     assert!((0..3).all(|_| atom.next().is_none()));
+/// }
+/// ```
+impl core::iter::FusedIterator for LispIter<'_> {}
+
+/// Yields the byte range of every comment in `input`, delimiters included.
+///
+/// Both `; line` and `#| block |#` comments are reported. Block comments nest, and an
+/// unclosed one runs to the end of input. The scan uses the same lexer as [`LispIter`],
+/// so a `;` inside a string literal is never taken for a comment.
+///
+/// ```
+/// let input = "(a \"; no\" ; one\n #| two #| nested |# |# b) #| open";
+/// let comments: Vec<_> = lisp_iter::comment_positions(input).map(|r| &input[r]).collect();
+/// assert_eq!(comments, ["; one", "#| two #| nested |# |#", "#| open"]);
+/// ```
+pub fn comment_positions(input: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut iter = LispIter::new(input);
+    core::iter::from_fn(move || loop {
+        match iter.lex()? {
+            (Token::LineComment | Token::BlockComment { .. }, span) => return Some(span),
+            _ => continue,
+        }
+    })
+}
+
+/// Writes `input` with every comment removed, as found by [`comment_positions`].
+///
+/// Everything else is copied as is, including a `;` inside a string literal and the
+/// newline ending a line comment, so [`LispIter`] reads the same atoms from the output. A
+/// block comment between two tokens with nothing else between them is replaced by a
+/// space, keeping them apart. Write into a `String` to get the result as text:
+///
+/// ```
+/// let input = "(a \"; b\") ; c\n#| d |#(e)#|f|#g";
+/// let mut out = String::new();
+/// lisp_iter::strip_comments(input, &mut out).unwrap();
+/// assert_eq!(out, "(a \"; b\") \n(e) g");
+/// assert!(lisp_iter::sexpr_eq(input, &out));
+/// ```
+pub fn strip_comments<W>(input: &str, out: &mut W) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    let mut end = 0;
+    for range in comment_positions(input) {
+        out.write_str(&input[end..range.start])?;
+        let touches = |c: Option<char>| c.is_some_and(|c| !c.is_whitespace());
+        if touches(input[..range.start].chars().next_back())
+            && touches(input[range.end..].chars().next())
+        {
+            out.write_char(' ')?;
+        }
+        end = range.end;
+    }
+    out.write_str(&input[end..])
+}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The input parses, but probably not the way it was meant to.
+    Warning,
+    /// The input is malformed; [`LispIter`] had to recover from it.
+    Error,
+}
+
+/// A problem found by [`check`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic<'a> {
+    pub severity: Severity,
+    /// Byte range of the offending source. Unclosed forms extend to the end of input.
+    pub span: Range<usize>,
+    pub error: ParseError<'a>,
+}
+
+impl core::fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.error),
+            Severity::Error => write!(f, "error: {}", self.error),
+        }
+    }
+}
+
+/// Returns the byte range of the first [`Atom::Identifier`] in `input` equal to `name`,
+/// however deeply nested.
+///
+/// Matching is exact and case-sensitive. Strings, keywords and comments never match, and
+/// neither does a token that reads as a number. See [`find_symbol_all`] for every
+/// occurrence.
+///
+/// ```
+/// let input = r#"(define (area r) (* pi r r)) ; r is the radius
+/// (print "r" (area 2))"#;
+/// assert_eq!(lisp_iter::find_symbol(input, "area"), Some(9..13));
+/// assert_eq!(lisp_iter::find_symbol(input, "radius"), None);
+/// ```
+pub fn find_symbol(input: &str, name: &str) -> Option<Range<usize>> {
+    find_symbol_all(input, name).next()
+}
+
+/// Like [`find_symbol`], but yields the range of every occurrence in source order.
+///
+/// The scan only lexes the input: no atoms are built apart from each token spelled like
+/// `name`, and strings and comments are stepped over as a whole.
+///
+/// ```
+/// let input = "(let ((x 1)) (+ x #| x |# \"x\" :x x))";
+/// let found: Vec<_> = lisp_iter::find_symbol_all(input, "x").collect();
+/// assert_eq!(found, [7..8, 16..17, 33..34]);
+/// ```
+pub fn find_symbol_all<'a>(
+    input: &'a str,
+    name: &'a str,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    let mut iter = LispIter::new(input);
+    core::iter::from_fn(move || loop {
+        let (token, span) = iter.lex()?;
+        let text = &iter.input[span.clone()];
+        if token == Token::Word
+            && text == name
+            && matches!(LispIter::word(text), Atom::Identifier(_))
+        {
+            return Some(span);
+        }
+    })
+}
+
+/// Counts the top-level expressions in `input`, such as for progress reporting.
+///
+/// Gives the same count as `LispIter::new(input).count()`, but only lexes the input: each
+/// list counts as one expression and is skipped by matching brackets, and no atoms are
+/// built.
+///
+/// ```
+/// assert_eq!(lisp_iter::top_level_count("(define x \")\") ; (a)\n x [1 2] #| y |#"), 3);
+/// ```
+pub fn top_level_count(input: &str) -> usize {
+    LispIter::new(input).count_extents()
+}
+
+/// Whether `input` ends with its expressions complete, for deciding when a REPL should
+/// evaluate or keep reading.
+///
+/// A stray closer makes the input [`Completeness::Invalid`] no matter what follows, while
+/// an unclosed list, quote or block comment makes it [`Completeness::Incomplete`]. Any
+/// number of complete expressions is [`Completeness::Complete`], and so is input with
+/// none, only whitespace and comments.
+///
+/// ```
+/// use lisp_iter::{is_complete, Completeness};
+///
+/// assert_eq!(is_complete("(define x 1) (print x) ; done"), Completeness::Complete);
+/// assert_eq!(is_complete("(print \"a)"), Completeness::Incomplete);
+/// assert_eq!(is_complete("(print (f x)"), Completeness::Incomplete);
+/// assert_eq!(is_complete("x) (y"), Completeness::Invalid);
+/// ```
+pub fn is_complete(input: &str) -> Completeness {
+    let mut iter = LispIter::new(input);
+    let mut open = OpenBrackets::new(&iter);
+    while let Some((token, span)) = iter.lex() {
+        match token {
+            Token::Open(bracket) => open.push(bracket),
+            Token::Close(closer) => match open.pop(span.start) {
+                Some(bracket) if bracket == closer => {}
+                _ => return Completeness::Invalid,
+            },
+            Token::Quote { closed: false, .. }
+            | Token::Bytes { closed: false }
+            | Token::BlockComment { closed: false } => return Completeness::Incomplete,
+            _ => {}
+        }
+    }
+    match open.depth {
+        0 => Completeness::Complete,
+        _ => Completeness::Incomplete,
+    }
+}
+
+/// The kinds of the brackets open at some point of a lexing pass, without allocating.
+///
+/// Only the innermost 64 kinds are kept, two bits each. Popping past them rescans the
+/// input from the start of the pass to recover the next 64.
+struct OpenBrackets<'s> {
+    start: LispIter<'s>,
+    kinds: u128,
+    known: usize,
+    depth: usize,
+}
+
+impl<'s> OpenBrackets<'s> {
+    const WINDOW: usize = 64;
+
+    /// Starts tracking a lexing pass about to be made with `iter`.
+    fn new(iter: &LispIter<'s>) -> Self {
+        OpenBrackets {
+            start: iter.clone(),
+            kinds: 0,
+            known: 0,
+            depth: 0,
+        }
+    }
+
+    fn push(&mut self, bracket: Bracket) {
+        self.kinds = self.kinds << 2 | bracket as u128;
+        self.known = (self.known + 1).min(Self::WINDOW);
+        self.depth += 1;
+    }
+
+    /// Closes the innermost bracket, for a closer at offset `end` into `input`, and
+    /// returns its kind, or `None` if nothing is open.
+    fn pop(&mut self, end: usize) -> Option<Bracket> {
+        if self.depth == 0 {
+            return None;
+        }
+        if self.known == 0 {
+            self.rescan(end);
+        }
+        let bracket = match self.kinds & 3 {
+            0 => Bracket::Paren,
+            1 => Bracket::Square,
+            _ => Bracket::Curly,
+        };
+        self.kinds >>= 2;
+        self.known -= 1;
+        self.depth -= 1;
+        Some(bracket)
+    }
+
+    /// Recovers the kinds of the innermost brackets still open at offset `end`.
+    fn rescan(&mut self, end: usize) {
+        let window = self.depth.min(Self::WINDOW);
+        let mut scan = self.start.clone();
+        let mut depth = 0usize;
+        while let Some((token, span)) = scan.lex() {
+            if span.start >= end {
+                break;
+            }
+            match token {
+                Token::Open(bracket) => {
+                    depth += 1;
+                    if let Some(level) = self.depth.checked_sub(depth).filter(|l| *l < window) {
+                        let shift = 2 * level;
+                        self.kinds = self.kinds & !(3 << shift) | (bracket as u128) << shift;
+                    }
+                }
+                Token::Close(_) => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        self.known = window;
+    }
+}
+
+/// Result of [`is_complete`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Completeness {
+    /// Every expression is closed.
+    Complete,
+    /// A list, quote or block comment is still open; more input may close it.
+    Incomplete,
+    /// A closer doesn't close anything, or is of the wrong kind; more input can't fix
+    /// that.
+    Invalid,
+}
+
+/// Walks the whole of `input` once and yields every problem found.
+///
+/// Reports unclosed lists, quotes and block comments and stray or mismatched closers as
+/// errors, and numeric-looking tokens that fail to parse or integers beyond `i64` as
+/// warnings. An unclosed list is reported once, at its outermost `(`, after everything
+/// else since it only shows at the end of input. Never allocates, and resynchronizes after
+/// each finding the same way [`LispIter`] does.
+///
+/// ```
+/// use lisp_iter::{check, Diagnostic, ParseError, Severity};
+///
+/// let found: Vec<_> = check("(a 1x) ] (b #| c").collect();
+/// assert_eq!(
+///     found,
+///     [
+///         Diagnostic {
+///             severity: Severity::Warning,
+///             span: 3..5,
+///             error: ParseError::InvalidNumber { offset: 3, token: "1x" },
+///         },
+///         Diagnostic {
+///             severity: Severity::Error,
+///             span: 7..8,
+///             error: ParseError::UnexpectedCloser { offset: 7 },
+///         },
+///         Diagnostic {
+///             severity: Severity::Error,
+///             span: 12..16,
+///             error: ParseError::UnclosedBlockComment { offset: 12 },
+///         },
+///         Diagnostic {
+///             severity: Severity::Error,
+///             span: 9..16,
+///             error: ParseError::UnclosedList { offset: 9 },
+///         },
+///     ]
+/// );
+/// assert_eq!(check("(fine [1 2] {:a 1})").count(), 0);
+/// ```
+pub fn check(input: &str) -> impl Iterator<Item = Diagnostic<'_>> + '_ {
+    check_with_config(input, ParseConfig::default())
+}
+
+/// Like [`check`], but with `config` deciding what counts as an error.
+///
+/// With [`ParseConfig::strict_numbers`] set, malformed numbers are errors.
+pub fn check_with_config(
+    input: &str,
+    config: ParseConfig,
+) -> impl Iterator<Item = Diagnostic<'_>> + '_ {
+    diagnostics(LispIter::with_config(input, config))
+}
+
+/// Iterator that rejects malformed input, see [`LispIter::strict_mode`].
+///
+/// Each expression is checked in full before it's yielded, so a list is either returned
+/// well-formed all the way down or replaced by the first error found in it. Iteration can
+/// continue after an error, resuming right after the offending expression.
+#[derive(Clone)]
+pub struct StrictLispIter<'s> {
+    iter: LispIter<'s>,
+}
+
+impl<'s> Iterator for StrictLispIter<'s> {
+    type Item = Result<Atom<'s>, ParseError<'s>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let atom = match self.iter.try_next()? {
+            Ok(atom) => atom,
+            Err(error) => return Some(Err(error)),
+        };
+        let nested = match &atom {
+            Atom::List(inner) | Atom::Vector(inner) | Atom::Map(inner) => {
+                diagnostics(inner.clone())
+                    .find(|diagnostic| diagnostic.severity == Severity::Error)
+                    .map(|diagnostic| diagnostic.error)
+            }
+            _ => None,
+        };
+        Some(match nested {
+            Some(error) => Err(error),
+            None => Ok(atom),
+        })
+    }
+}
+
+/// Diagnostics for everything below `iter`, with offsets relative to its outermost input.
+fn diagnostics(mut iter: LispIter<'_>) -> impl Iterator<Item = Diagnostic<'_>> + '_ {
+    let mut open = OpenBrackets::new(&iter);
+    let mut list_start = 0;
+    core::iter::from_fn(move || loop {
+        let Some((token, span)) = iter.lex() else {
+            if open.depth == 0 {
+                return None;
+            }
+            open.depth = 0;
+            let end = iter.pos(iter.end());
+            return Some(Diagnostic {
+                severity: Severity::Error,
+                span: list_start..end,
+                error: ParseError::UnclosedList { offset: list_start },
+            });
+        };
+        let offset = iter.pos(span.start);
+        let (severity, error) = match token {
+            Token::Open(bracket) => {
+                if open.depth == 0 {
+                    list_start = offset;
+                }
+                open.push(bracket);
+                let odd = bracket == Bracket::Curly
+                    && (iter.clone().close_list())
+                        .is_some_and(|end| iter.sublist(span.end..end).count_extents() % 2 == 1);
+                match odd {
+                    true => (Severity::Error, ParseError::OddMapLength { offset }),
+                    false => continue,
+                }
+            }
+            Token::Close(closer) => match open.pop(span.start) {
+                Some(bracket) if bracket == closer => continue,
+                Some(bracket) => {
+                    let error = ParseError::MismatchedCloser {
+                        offset,
+                        expected: bracket.closer(),
+                        found: closer.closer(),
+                    };
+                    (Severity::Error, error)
+                }
+                None => (Severity::Error, ParseError::UnexpectedCloser { offset }),
+            },
+            Token::Quote { closed: false, .. } | Token::Bytes { closed: false } => {
+                (Severity::Error, ParseError::UnclosedQuote { offset })
+            }
+            Token::Bytes { closed: true } if !iter.valid_bytes(true, &span) => {
+                (Severity::Error, ParseError::InvalidBytes { offset })
+            }
+            Token::BlockComment { closed: false } => {
+                (Severity::Error, ParseError::UnclosedBlockComment { offset })
+            }
+            Token::Word if LispIter::is_invalid_number(iter.text(span.clone())) => {
+                let severity = match iter.config.strict_numbers {
+                    true => Severity::Error,
+                    false => Severity::Warning,
+                };
+                let error = ParseError::InvalidNumber {
+                    offset,
+                    token: iter.text(span.clone()),
+                };
+                (severity, error)
+            }
+            Token::Word if LispIter::is_integer_overflow(iter.text(span.clone())) => {
+                let severity = match iter.config.strict_numbers {
+                    true => Severity::Error,
+                    false => Severity::Warning,
+                };
+                (severity, ParseError::IntegerOverflow { offset })
+            }
+            _ => continue,
+        };
+        return Some(Diagnostic {
+            severity,
+            span: offset..iter.pos(span.end),
+            error,
+        });
+    })
+}
+
+/// Error returned by [`parse_one`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseOneError<'a> {
+    /// The input holds no expression, only whitespace and comments.
+    Empty,
+    /// Something other than whitespace and comments follows the expression.
+    TrailingInput { offset: usize },
+    /// The expression itself is malformed.
+    Parse(ParseError<'a>),
+}
+
+impl<'a> From<ParseError<'a>> for ParseOneError<'a> {
+    fn from(e: ParseError<'a>) -> Self {
+        ParseOneError::Parse(e)
+    }
+}
+
+impl core::fmt::Display for ParseOneError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("expected an expression, found end of input"),
+            Self::TrailingInput { offset } => write!(f, "unexpected input at byte {}", offset),
+            Self::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl core::error::Error for ParseOneError<'_> {}
+
+/// Parses `input` as exactly one expression.
+///
+/// Whitespace and comments may surround the expression, but anything else after it is
+/// [`ParseOneError::TrailingInput`]. Unlike a plain [`LispIter`], errors anywhere inside
+/// the expression are reported, as [`LispIter::try_next`] would report them on the way
+/// down.
+///
+/// ```
+/// use lisp_iter::{parse_one, ParseError, ParseOneError};
+///
+/// assert!(parse_one("  (a b) ; trailing comment").is_ok());
+/// assert!(parse_one("(a b) #| block |#\n").is_ok());
+/// assert_eq!(parse_one("(a b) c").unwrap_err(), ParseOneError::TrailingInput { offset: 6 });
+/// let after_comment = parse_one("(a b) ; c\n d").unwrap_err();
+/// assert_eq!(after_comment, ParseOneError::TrailingInput { offset: 11 });
+/// assert_eq!(parse_one("; only a comment").unwrap_err(), ParseOneError::Empty);
+/// assert_eq!(
+///     parse_one("(a (b)").unwrap_err(),
+///     ParseOneError::Parse(ParseError::UnclosedList { offset: 0 })
+/// );
+/// ```
+pub fn parse_one(input: &str) -> Result<Atom<'_>, ParseOneError<'_>> {
+    let mut iter = LispIter::new(input);
+    let atom = iter.try_next().ok_or(ParseOneError::Empty)??;
+    if let Atom::List(list) | Atom::Vector(list) | Atom::Map(list) = &atom {
+        let mut errors = diagnostics(list.clone()).filter(|d| d.severity == Severity::Error);
+        if let Some(diagnostic) = errors.next() {
+            return Err(diagnostic.error.into());
+        }
+    }
+    while let Some((token, span)) = iter.lex() {
+        match token {
+            Token::LineComment | Token::BlockComment { closed: true } => continue,
+            _ => return Err(ParseOneError::TrailingInput { offset: span.start }),
+        }
+    }
+    Ok(atom)
+}
+
+/// A location in the source, as computed by [`position_of`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// 1-based line number.
+    pub line: u32,
+    /// 1-based column, counted in chars. A tab counts as one column.
+    pub column: u32,
+    /// Byte offset into the input.
+    pub byte: usize,
+}
+
+impl core::fmt::Display for Position {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Computes the line and column of the byte offset `byte` in `input`.
+///
+/// Only `\n` starts a new line, so `\r\n` counts once and a lone `\r` is an ordinary
+/// char. Offsets past the end are clamped to the end of input, and an offset inside a
+/// multi-byte char points at that char. This is O(n) in `byte`.
+///
+/// ```
+/// use lisp_iter::position_of;
+///
+/// let input = "(é\r\n  ß x)";
+/// assert_eq!(position_of(input, 3).to_string(), "1:3");
+/// let x = position_of(input, 10);
+/// assert_eq!((x.line, x.column, x.byte), (2, 5, 10));
+/// assert_eq!(position_of(input, 8).column, position_of(input, 7).column);
+/// assert_eq!(position_of(input, 100).to_string(), "2:7");
+/// ```
+pub fn position_of(input: &str, byte: usize) -> Position {
+    let byte = byte.min(input.len());
+    let mut line = 1;
+    let mut column = 1;
+    for (_, c) in input
+        .char_indices()
+        .take_while(|(i, c)| i + c.len_utf8() <= byte)
+    {
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    Position { line, column, byte }
+}
+
+/// Writes the line of `input` containing `span` with the span underlined, rustc style:
+///
+/// ```text
+/// 3 | (resize 800 six-hundred)
+///   |             ^^^^^^^^^^^
+/// ```
+///
+/// A span running past the end of its line is clipped to it and marked with `...`, and
+/// an empty span gets a single `^`. Tabs before the span are copied into the underline so
+/// it lines up however they're displayed, and every other char counts as one column.
+/// The `\r` of a `\r\n` line ending isn't shown; any other `\r` is an ordinary char.
+///
+/// ```
+/// let mut out = String::new();
+/// lisp_iter::render_snippet("(a\r\n\t(é\tbad))", 9..12, &mut out).unwrap();
+/// assert_eq!(out, "2 | \t(é\tbad))\n  | \t  \t^^^\n");
+///
+/// out.clear();
+/// lisp_iter::render_snippet("(a\r\n\r\n)", 4..5, &mut out).unwrap();
+/// assert_eq!(out, "2 | \n  | ^\n");
+///
+/// out.clear();
+/// lisp_iter::render_snippet("ab\r\r\n", 3..4, &mut out).unwrap();
+/// assert_eq!(out, "1 | ab\r\n  |    ^\n");
+///
+/// let input = "(list\n  (a b\n  c))";
+/// out.clear();
+/// lisp_iter::render_snippet(input, 8..16, &mut out).unwrap();
+/// assert_eq!(out, "2 |   (a b\n  |   ^^^^...\n");
+/// out.clear();
+/// lisp_iter::render_snippet(input, 11..11, &mut out).unwrap();
+/// assert_eq!(out, "2 |   (a b\n  |      ^\n");
+///
+/// let input = "x\n".repeat(11) + "(oops";
+/// out.clear();
+/// lisp_iter::render_snippet(&input, 22..27, &mut out).unwrap();
+/// assert_eq!(out, "12 | (oops\n   | ^^^^^\n");
+/// ```
+pub fn render_snippet<W>(input: &str, span: Range<usize>, out: &mut W) -> core::fmt::Result
+where
+    W: core::fmt::Write,
+{
+    let mut start = span.start.min(input.len());
+    while !input.is_char_boundary(start) {
+        start -= 1;
+    }
+    let line_start = input[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = input[start..].find('\n').map_or(input.len(), |i| start + i);
+    let line = &input[line_start..line_end];
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let number = position_of(input, start).line;
+
+    let mut gutter = 0;
+    let mut n = number;
+    while n > 0 {
+        gutter += 1;
+        n /= 10;
+    }
+
+    writeln!(out, "{} | {}", number, line)?;
+    write!(out, "{:gutter$} | ", "", gutter = gutter)?;
+    for c in input[line_start..start].chars() {
+        out.write_char(if c == '\t' { '\t' } else { ' ' })?;
+    }
+    let span_end = span.end.min(input.len());
+    // The span may start on the line's ending, past its visible text.
+    let visible_end = line_start + line.len();
+    let end = span_end.clamp(start.min(visible_end), visible_end);
+    let carets = input[start.min(end)..end].chars().count().max(1);
+    for _ in 0..carets {
+        out.write_char('^')?;
+    }
+    if span_end > line_end {
+        out.write_str("...")?;
+    }
+    writeln!(out)
+}
+
+/// Yields the byte range of each top-level expression in `input`, parens and quotes
+/// included.
+///
+/// The ranges match [`Span::outer`] as given by [`LispIter::next_spanned`], but lists are
+/// only scanned for their closing `)`, never parsed into atoms. Comments are skipped, and
+/// an unclosed form at the end yields a range ending at the end of input.
+///
+/// ```
+/// let input = "(a (b)) ; c\n\"s\" [x] (open";
+/// let forms: Vec<_> = lisp_iter::top_level_spans(input).map(|r| &input[r]).collect();
+/// assert_eq!(forms, ["(a (b))", "\"s\"", "[x]", "(open"]);
+/// ```
+pub fn top_level_spans(input: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut iter = LispIter::new(input);
+    core::iter::from_fn(move || iter.next_extent())
+}
+
+/// Formats an [`Atom`] as indented, multi-line source, for error messages or REPL output.
+///
+/// A list, vector or map is written on one line if it fits within `max_line_width`
+/// columns, counting its indentation. Otherwise its elements go on lines of their own,
+/// indented `indent_width` past the opening bracket's line, and each is laid out the same
+/// way. Other atoms are always inline. Quotes are written with `"`s, so a `:keyword`
+/// comes out as `"keyword"`, or as a raw string like `r#"say "hi""#` if they hold a `"`.
+///
+/// Formatting streams straight to the output: widths are measured by dry runs that stop
+/// as soon as they run over, so no buffer is needed. Like [`Atom`]'s `Debug`, it recurses
+/// into nested containers, so beyond a depth of [`DEBUG_DEPTH_LIMIT`] their contents are
+/// elided as `(...)`.
+///
+/// To format into any [`core::fmt::Write`], such as a `String` being built, use `write!`
+/// with the printer. It isn't a [`core::fmt::Write`] itself: that would have it take in
+/// text rather than atoms, and since atoms borrow the text they're parsed from, it would
+/// have to buffer everything written to it, which needs `alloc`.
+///
+/// ```
+/// use core::fmt::Write;
+/// use lisp_iter::{LispIter, PrettyPrinter, DEBUG_DEPTH_LIMIT};
+///
+/// let atom = LispIter::new("(define (square x) (* x x))").next().unwrap();
+/// let printer = PrettyPrinter { max_line_width: 20, ..PrettyPrinter::new(atom) };
+/// assert_eq!(printer.to_string(), "(define\n  (square x)\n  (* x x))");
+///
+/// let deep = "(".repeat(20_000) + &")".repeat(20_000);
+/// let atom = LispIter::new(&deep).next().unwrap();
+/// let elided = "(".repeat(DEBUG_DEPTH_LIMIT) + "(...)" + &")".repeat(DEBUG_DEPTH_LIMIT);
+/// assert_eq!(PrettyPrinter::new(atom).to_string(), elided);
+///
+/// let mut out = String::from("=> ");
+/// let atom = LispIter::new("(a b)").next().unwrap();
+/// write!(out, "{}", PrettyPrinter::new(atom)).unwrap();
+/// assert_eq!(out, "=> (a b)");
+///
+/// let atom = LispIter::new(r##"(:k r#"a"b"#)"##).next().unwrap();
+/// assert_eq!(PrettyPrinter::new(atom).to_string(), r##"("k" r#"a"b"#)"##);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PrettyPrinter<'a> {
+    /// The atom to format.
+    pub atom: Atom<'a>,
+    /// Columns to indent each level by. Defaults to 2.
+    pub indent_width: usize,
+    /// Column budget for writing a list on one line. Defaults to 80.
+    pub max_line_width: usize,
+}
+
+impl<'a> PrettyPrinter<'a> {
+    /// Creates a printer for `atom` with the default widths.
+    pub fn new(atom: Atom<'a>) -> Self {
+        PrettyPrinter {
+            atom,
+            indent_width: 2,
+            max_line_width: 80,
+        }
+    }
+
+    fn write_atom(
+        &self,
+        f: &mut core::fmt::Formatter<'_>,
+        atom: &Atom<'_>,
+        indent: usize,
+        depth: usize,
+    ) -> core::fmt::Result {
+        let Some((open, close, iter)) = brackets(atom) else {
+            return write_inline(f, atom, depth);
+        };
+        let mut budget = Budget(self.max_line_width.saturating_sub(indent));
+        if iter.is_empty()
+            || depth >= DEBUG_DEPTH_LIMIT
+            || write_inline(&mut budget, atom, depth).is_ok()
+        {
+            return write_inline(f, atom, depth);
+        }
+        f.write_str(open)?;
+        let inner = indent + self.indent_width;
+        for (i, element) in iter.clone().enumerate() {
+            if i > 0 {
+                write!(f, "\n{:inner$}", "")?;
+            }
+            self.write_atom(f, &element, inner, depth + 1)?;
+        }
+        f.write_str(close)
+    }
+}
+
+impl core::fmt::Display for PrettyPrinter<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_atom(f, &self.atom, 0, 0)
+    }
+}
+
+/// Shorthand for a [`PrettyPrinter`] indenting by `indent` columns.
+pub fn pretty_print<'a>(atom: &Atom<'a>, indent: usize) -> PrettyPrinter<'a> {
+    PrettyPrinter {
+        indent_width: indent,
+        ..PrettyPrinter::new(atom.clone())
+    }
+}
+
+/// Brackets and contents of a list, vector or map, or `None` for any other atom.
+fn brackets<'i, 'a>(atom: &'i Atom<'a>) -> Option<(&'static str, &'static str, &'i LispIter<'a>)> {
+    match atom {
+        Atom::List(iter) => Some(("(", ")", iter)),
+        Atom::Vector(iter) => Some(("[", "]", iter)),
+        Atom::Map(iter) => Some(("{", "}", iter)),
+        _ => None,
+    }
+}
+
+/// Writes `atom`, found at nesting `depth`, on a single line.
+fn write_inline<W: core::fmt::Write>(
+    out: &mut W,
+    atom: &Atom<'_>,
+    depth: usize,
+) -> core::fmt::Result {
+    let Some((open, close, iter)) = brackets(atom) else {
+        return match atom {
+            Atom::Identifier(s) => out.write_str(s),
+            Atom::Quote(s) => write_quote(out, s),
+            Atom::Integer(v) => write!(out, "{}", v),
+            Atom::Float(v) => write!(out, "{:?}", v),
+            Atom::Float32(v) => write!(out, "{:?}f32", v),
+            Atom::Rational(n, d) => write!(out, "{}/{}", n, d),
+            Atom::Bytes(hex) => write!(out, "#\"{}\"", hex.escape_ascii()),
+            _ => unreachable!(),
+        };
+    };
+    out.write_str(open)?;
+    if depth >= DEBUG_DEPTH_LIMIT && !iter.is_empty() {
+        out.write_str("...")?;
+        return out.write_str(close);
+    }
+    for (i, element) in iter.clone().enumerate() {
+        if i > 0 {
+            out.write_char(' ')?;
+        }
+        write_inline(out, &element, depth + 1)?;
+    }
+    out.write_str(close)
+}
+
+/// Writes `s` as a string: between `"`s, or if it holds a `"` itself, as a raw string
+/// with one more `#` than any run of them following a `"` in it.
+fn write_quote<W: core::fmt::Write>(out: &mut W, s: &str) -> core::fmt::Result {
+    if !s.contains('"') {
+        return write!(out, "\"{}\"", s);
+    }
+    let hashes = s
+        .split('"')
+        .skip(1)
+        .map(|after| after.len() - after.trim_start_matches('#').len())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    write!(out, "r{0:#<hashes$}\"{s}\"{0:#<hashes$}", "")
+}
+
+/// Writer that only counts columns, failing once more than its budget has been written.
+struct Budget(usize);
+
+impl core::fmt::Write for Budget {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.0 = self
+            .0
+            .checked_sub(s.chars().count())
+            .ok_or(core::fmt::Error)?;
+        Ok(())
+    }
+}
+
+impl Atom<'_> {
+    /// Checks the atom against a pattern written as source text, like `(define _ _)`.
+    ///
+    /// In the pattern, `_` matches any one expression and `...` at the end of a list any
+    /// number of remaining ones. Anything else must match exactly, as with `==`, with
+    /// brackets of the same kind. Subject and pattern are walked side by side without
+    /// allocating, and the parts of the subject matched by `_` or `...` are skipped
+    /// without being parsed.
+    ///
+    /// A pattern that isn't exactly one well-formed expression, or has `...` anywhere but
+    /// at the end of a list, is a [`ShapeError`], whether or not the atom would match.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let atom = LispIter::new("(let ((x 1) (y 2)) (+ x y))").next().unwrap();
+    /// assert_eq!(atom.matches_shape("(let ((_ _) ...) ...)"), Ok(true));
+    /// assert_eq!(atom.matches_shape("(let ((_ _)) _)"), Ok(false));
+    /// assert!(atom.matches_shape("(let ... _)").is_err());
+    /// ```
+    pub fn matches_shape<'p>(&self, pattern: &'p str) -> Result<bool, ShapeError<'p>> {
+        let pattern_atom = parse_one(pattern).map_err(ShapeError::Pattern)?;
+        let mut iter = LispIter::new(pattern);
+        let mut depth = 0usize;
+        while let Some((token, span)) = iter.next_token() {
+            match token {
+                Token::Open(_) => depth += 1,
+                Token::Close(_) => depth -= 1,
+                Token::Word if &pattern[span.clone()] == "..." => {
+                    if depth == 0 || !matches!(iter.next_token(), Some((Token::Close(_), _))) {
+                        return Err(ShapeError::MisplacedEllipsis { offset: span.start });
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+        Ok(shape_matches(&pattern_atom, self.clone()))
+    }
+}
+
+/// Whether `subject` matches the well-formed `pattern`, see [`Atom::matches_shape`].
+fn shape_matches(pattern: &Atom<'_>, subject: Atom<'_>) -> bool {
+    if pattern.eq_identifier("_") {
+        return true;
+    }
+    match (pattern, subject) {
+        (Atom::List(p), Atom::List(s))
+        | (Atom::Vector(p), Atom::Vector(s))
+        | (Atom::Map(p), Atom::Map(s)) => shape_elements_match(p.clone(), s),
+        (pattern, subject) => pattern.shallow_eq(&subject),
+    }
+}
+
+/// Whether the elements of a list match those of a pattern list.
+fn shape_elements_match(mut pattern: LispIter<'_>, mut subject: LispIter<'_>) -> bool {
+    for element in pattern.by_ref() {
+        if element.eq_identifier("...") {
+            return true;
+        }
+        let matched = match element.eq_identifier("_") {
+            true => subject.skip_expr(),
+            false => subject
+                .next()
+                .is_some_and(|atom| shape_matches(&element, atom)),
+        };
+        if !matched {
+            return false;
+        }
+    }
+    subject.is_empty()
+}
+
+/// Error returned by [`Atom::matches_shape`]: the pattern is malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapeError<'p> {
+    /// The pattern isn't exactly one well-formed expression.
+    Pattern(ParseOneError<'p>),
+    /// A `...` that isn't the last element of a list.
+    MisplacedEllipsis { offset: usize },
+}
+
+impl core::fmt::Display for ShapeError<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Pattern(e) => write!(f, "invalid pattern: {}", e),
+            Self::MisplacedEllipsis { offset } => {
+                write!(f, "`...` not at the end of a list at byte {}", offset)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ShapeError<'_> {}
+
+/// Whether `a` and `b` hold the same expressions, regardless of whitespace and comments.
+///
+/// Atoms compare as with [`Atom`]'s `PartialEq`, so `1.50` equals `1.5` but `1` doesn't
+/// equal `1.0`, while strings compare by their raw text. Brackets must match exactly:
+/// `(a b)` differs from `(a (b))` and from `[a b]`. Tokens that don't make an atom, like
+/// malformed bytes, compare by their raw text, so every input equals itself. See
+/// [`sexpr_eq_why`] to find out where two inputs differ.
+///
+/// ```
+/// assert!(lisp_iter::sexpr_eq("(a  b)", "( a ; hi\n b )"));
+/// assert!(!lisp_iter::sexpr_eq("(a b)", "(a (b))"));
+/// assert!(lisp_iter::sexpr_eq("#\"zz\"", "#\"zz\""));
+/// assert!(lisp_iter::sexpr_eq("(f #\"zz\" 'x)", "(f  #\"zz\"\n'x)"));
+/// assert!(!lisp_iter::sexpr_eq("#\"zz\"", "#\"ZZ\""));
+/// ```
+pub fn sexpr_eq(a: &str, b: &str) -> bool {
+    sexpr_eq_why(a, b).is_none()
+}
+
+/// Like [`sexpr_eq`], but returns the byte ranges of the first differing token in each
+/// input, or `None` if they're equal.
+///
+/// When one input runs out first, its range is empty and sits at its end.
+pub fn sexpr_eq_why(a: &str, b: &str) -> Option<(Range<usize>, Range<usize>)> {
+    let (mut a, mut b) = (LispIter::new(a), LispIter::new(b));
+    loop {
+        let (x, y) = (a.next_token(), b.next_token());
+        let equal = match (x.clone(), y.clone()) {
+            (None, None) => return None,
+            (Some((Token::Open(x), _)), Some((Token::Open(y), _))) => x == y,
+            (Some((Token::Close(x), _)), Some((Token::Close(y), _))) => x == y,
+            (Some((x, xs)), Some((y, ys))) => {
+                match (a.leaf(x, xs.clone()), b.leaf(y, ys.clone())) {
+                    (Some(x), Some(y)) => x.shallow_eq(&y),
+                    _ => a.input[xs] == b.input[ys],
+                }
+            }
+            _ => false,
+        };
+        if !equal {
+            let end = |iter: &LispIter<'_>| iter.input.len()..iter.input.len();
+            let x = x.map_or_else(|| end(&a), |(_, span)| span);
+            let y = y.map_or_else(|| end(&b), |(_, span)| span);
+            return Some((x, y));
+        }
+    }
+}
+
+/// Finds the first place where `a` and `b` differ structurally, or returns `None` if
+/// they're equal as with [`sexpr_eq`].
+///
+/// Whitespace and comments don't matter and numbers compare by value. When a list ends
+/// early on one side, the difference is at the index where it ran out.
+///
+/// ```
+/// let site = lisp_iter::diff("(a b (5 6))", "(a b ; three\n (foo 6))").unwrap();
+/// assert_eq!(site.path().collect::<Vec<_>>(), [0, 2, 0]);
+/// assert_eq!((site.a.clone(), site.b.clone()), (6..7, 15..18));
+/// assert_eq!(site.to_string(), "at [0][2][0]: expected integer 5, found identifier foo");
+///
+/// let site = lisp_iter::diff("(a b)", "(a b (c d))").unwrap();
+/// assert_eq!(site.to_string(), "at [0][2]: expected end of list, found list");
+/// ```
+pub fn diff<'a>(a: &'a str, b: &'a str) -> Option<DiffSite<'a>> {
+    let (x, y) = sexpr_eq_why(a, b)?;
+    Some(DiffSite {
+        a: LispIter::new(a).subtree(x),
+        b: LispIter::new(b).subtree(y),
+        input_a: a,
+        input_b: b,
+    })
+}
+
+/// Where two inputs first differ, see [`diff`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffSite<'a> {
+    /// Byte range of the differing expression in the first input. It's empty where that
+    /// input ran out, and covers just the bracket where a list ended early.
+    pub a: Range<usize>,
+    /// Same for the second input.
+    pub b: Range<usize>,
+    input_a: &'a str,
+    input_b: &'a str,
+}
+
+impl<'a> DiffSite<'a> {
+    /// Indices of the expressions leading to the difference, from the top level down. The
+    /// first is the index of the top-level expression, the last that of the difference
+    /// within its list.
+    ///
+    /// Worked out from the first input when called, so it doesn't need to be stored.
+    pub fn path(&self) -> DiffPath<'a> {
+        DiffPath {
+            iter: Some(LispIter::new(self.input_a)),
+            pos: self.a.start,
+        }
+    }
+}
+
+/// Writes the difference like `at [0][2]: expected integer 5, found identifier foo`.
+impl core::fmt::Display for DiffSite<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("at ")?;
+        for index in self.path() {
+            write!(f, "[{}]", index)?;
+        }
+        f.write_str(": expected ")?;
+        describe(f, self.input_a, self.a.clone())?;
+        f.write_str(", found ")?;
+        describe(f, self.input_b, self.b.clone())
+    }
+}
+
+/// Names the expression at `span` for [`DiffSite`]'s `Display`.
+fn describe(
+    f: &mut core::fmt::Formatter<'_>,
+    input: &str,
+    span: Range<usize>,
+) -> core::fmt::Result {
+    let text = &input[span];
+    if text.is_empty() {
+        return f.write_str("end of input");
+    }
+    if text.starts_with([')', ']', '}']) {
+        return f.write_str("end of list");
+    }
+    match LispIter::new(text).next().map(|atom| atom.kind()) {
+        Some(kind @ (AtomKind::List | AtomKind::Vector | AtomKind::Map)) => write!(f, "{}", kind),
+        Some(kind) => write!(f, "{} {}", kind, text),
+        None => f.write_str(text),
+    }
+}
+
+/// Iterator over the path of a [`DiffSite`], see [`DiffSite::path`].
+#[derive(Clone)]
+pub struct DiffPath<'a> {
+    /// Iterator over the list holding the next level, or `None` once done.
+    iter: Option<LispIter<'a>>,
+    /// Offset of the difference.
+    pos: usize,
+}
+
+impl Iterator for DiffPath<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let iter = self.iter.as_mut()?;
+        let mut index = 0;
+        while let Some(extent) = iter.next_extent() {
+            let (start, end) = (iter.base + extent.start, iter.base + extent.end);
+            if start >= self.pos {
+                break;
+            }
+            if self.pos < end {
+                let mut inner = iter.clone();
+                inner.seek(extent.start + 1);
+                let close = inner.close_list().unwrap_or(iter.input.len());
+                *iter = iter.sublist(extent.start + 1..close);
+                return Some(index);
+            }
+            index += 1;
+        }
+        self.iter = None;
+        Some(index)
+    }
+}
+
+impl<'s> LispIter<'s> {
+    /// Widens the range of a token to the whole expression it starts.
+    fn subtree(mut self, token: Range<usize>) -> Range<usize> {
+        if !self.input[token.clone()].starts_with(['(', '[', '{']) {
+            return token;
+        }
+        self.seek(token.end);
+        match self.close_list() {
+            Some(end) => token.start..end + ')'.len_utf8(),
+            None => token.start..self.input.len(),
+        }
+    }
+
+    /// Lexes the next token that isn't a comment.
+    fn next_token(&mut self) -> Option<(Token, Range<usize>)> {
+        loop {
+            match self.lex()? {
+                (Token::LineComment | Token::BlockComment { .. }, _) => continue,
+                token => return Some(token),
+            }
+        }
+    }
+
+    /// Builds the atom of a word or quote token, or returns `None` for any other token.
+    fn leaf(&mut self, token: Token, span: Range<usize>) -> Option<Atom<'s>> {
+        match token {
+            Token::Word | Token::Quote { .. } | Token::Bytes { .. } => {
+                self.parse(token, span, true)?.atom
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Matches an atom against list patterns, binding the parts of the first one that fits.
+///
+/// Each arm is a parenthesized pattern, matched against an [`Atom::List`] element by
+/// element. The list must have exactly as many elements as the pattern, unless it ends in
+/// `..`. The last arm must be `_`, taken when nothing else matches. The atom is cloned for
+/// each arm tried, so it's never consumed, and nothing allocates.
+///
+/// Elements are separated by commas:
+///
+/// | Element            | Matches                                      | Binds           |
+/// |--------------------|----------------------------------------------|-----------------|
+/// | `ident "define"`   | the identifier `define`                      |                 |
+/// | `str "on"`         | the quote `"on"` (or `:on`)                  |                 |
+/// | `int 0`            | the integer `0`                              |                 |
+/// | `_`                | any atom                                     |                 |
+/// | `name: ident`      | any identifier                               | `&str`          |
+/// | `name: str`        | any quote                                    | `&str`          |
+/// | `name: int`        | any integer                                  | `i64`           |
+/// | `name: float`      | any float                                    | `f64`           |
+/// | `name: list`       | any list                                     | [`LispIter`]    |
+/// | `name: _`          | any atom                                     | [`Atom`]        |
+/// | `( ... )`          | a list matching the nested pattern           | its bindings    |
+/// | `..` or `.. name`  | the rest of the list, last only              | [`LispIter`]    |
+///
+/// ```
+/// use lisp_iter::{lisp_match, LispIter};
+///
+/// fn describe(source: &str) -> String {
+///     let atom = LispIter::new(source).next().unwrap();
+///     lisp_match!(atom, {
+///         (ident "define", (name: ident, .. args), ..) => {
+///             format!("function {} of {}", name, args.count())
+///         },
+///         (ident "define", name: ident, value: int) => format!("{} = {}", name, value),
+///         (ident "define", ..) => "malformed define".to_string(),
+///         _ => "something else".to_string(),
+///     })
+/// }
+///
+/// assert_eq!(describe("(define (add a b) (+ a b))"), "function add of 2");
+/// assert_eq!(describe("(define answer 42)"), "answer = 42");
+/// assert_eq!(describe("(define answer 42 43)"), "malformed define");
+/// assert_eq!(describe("(define \"answer\" 42)"), "malformed define");
+/// assert_eq!(describe("answer"), "something else");
+/// ```
+///
+/// Arms are tried in order, and a list that's too short, too long, or has an element of the
+/// wrong kind falls through to the next one:
+///
+/// ```
+/// use lisp_iter::{lisp_match, LispIter};
+///
+/// let atom = LispIter::new("(rgb 1.0 0.5 (alpha 0.25))").next().unwrap();
+/// let rgba = lisp_match!(&atom, {
+///     (ident "rgb", r: float, g: float, b: float) => (r, g, b, 1.0),
+///     (ident "rgb", r: float, g: float) => (r, g, 0.0, 1.0),
+///     (ident "rgb", r: float, g: float, (ident "alpha", a: float)) => (r, g, 0.0, a),
+///     _ => (0.0, 0.0, 0.0, 0.0),
+/// });
+/// assert_eq!(rgba, (1.0, 0.5, 0.0, 0.25));
+/// ```
+#[macro_export]
+macro_rules! lisp_match {
+    ($atom:expr, { $($arms:tt)* }) => {{
+        let atom = $atom;
+        #[allow(unused_labels)]
+        let result = 'lisp_match: { $crate::lisp_match!(@arms atom 'lisp_match ; $($arms)*) };
+        result
+    }};
+
+    (@arms $atom:ident $outer:lifetime ; _ => $body:expr $(,)?) => { $body };
+    (@arms $atom:ident $outer:lifetime ; ( $($pattern:tt)* ) => $body:expr , $($rest:tt)*) => {{
+        'arm: {
+            let Some(mut iter) = $atom.clone().into_list_iter() else {
+                break 'arm;
+            };
+            $crate::lisp_match!(@elems iter 'arm ; $($pattern)*);
+            break $outer $body;
+        }
+        $crate::lisp_match!(@arms $atom $outer ; $($rest)*)
+    }};
+
+    (@elems $it:ident $l:lifetime ;) => {
+        if !$it.is_empty() {
+            break $l;
+        }
+    };
+    (@elems $it:ident $l:lifetime ; .. $(,)?) => {};
+    (@elems $it:ident $l:lifetime ; .. $name:ident $(,)?) => {
+        #[allow(unused_mut)]
+        let mut $name = $it;
+    };
+    (@elems $it:ident $l:lifetime ; ident $lit:literal $(, $($rest:tt)*)?) => {
+        if !$it.next().is_some_and(|atom| atom.eq_identifier($lit)) {
+            break $l;
+        }
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; str $lit:literal $(, $($rest:tt)*)?) => {
+        if !$it.next().is_some_and(|atom| atom.eq_quote($lit)) {
+            break $l;
+        }
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; int $lit:literal $(, $($rest:tt)*)?) => {
+        if !$it.next().is_some_and(|atom| atom.eq_integer($lit)) {
+            break $l;
+        }
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; _ $(, $($rest:tt)*)?) => {
+        if $it.next().is_none() {
+            break $l;
+        }
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; $name:ident : ident $(, $($rest:tt)*)?) => {
+        let Some($name) = $it.next().and_then(|atom| atom.as_identifier()) else {
+            break $l;
+        };
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; $name:ident : str $(, $($rest:tt)*)?) => {
+        let Some($name) = $it.next().and_then(|atom| atom.as_quote()) else {
+            break $l;
+        };
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; $name:ident : int $(, $($rest:tt)*)?) => {
+        let Some($name) = $it.next().and_then(|atom| atom.as_integer()) else {
+            break $l;
+        };
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; $name:ident : float $(, $($rest:tt)*)?) => {
+        let Some($name) = $it.next().and_then(|atom| atom.as_float()) else {
+            break $l;
+        };
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; $name:ident : list $(, $($rest:tt)*)?) => {
+        #[allow(unused_mut)]
+        let Some(mut $name) = $it.next().and_then(|atom| atom.into_list_iter()) else {
+            break $l;
+        };
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; $name:ident : _ $(, $($rest:tt)*)?) => {
+        let Some($name) = $it.next() else {
+            break $l;
+        };
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+    (@elems $it:ident $l:lifetime ; ( $($inner:tt)* ) $(, $($rest:tt)*)?) => {
+        let Some(mut list) = $it.next().and_then(|atom| atom.into_list_iter()) else {
+            break $l;
+        };
+        $crate::lisp_match!(@elems list $l ; $($inner)*);
+        $crate::lisp_match!(@elems $it $l ; $($($rest)*)?);
+    };
+}
+
+/// Builds an [`Atom`] from Lisp written inline, for tests and fixtures.
+///
+/// The whole input is taken verbatim with `stringify!`, which keeps its spacing, and parsed
+/// like any other input, so the atom borrows a `&'static str`. A single expression becomes
+/// that atom; several become an [`Atom::List`] of them, as if wrapped in parentheses.
+/// Nothing is checked at compile time, beyond the text having to be valid Rust tokens: no
+/// `'` quotes, `;` comments or unbalanced brackets.
+///
+/// ```
+/// use lisp_iter::{atom, Atom, LispIter};
+///
+/// assert!(atom!(42).eq_integer(42));
+/// assert!(atom!(-1.5).eq_float(-1.5));
+/// assert!(atom!(:width).eq_quote("width"));
+/// assert!(atom!("text").eq_quote("text"));
+/// assert_eq!(atom!(a b c), LispIter::new("(a b c)").next().unwrap());
+/// assert_eq!(atom!((define (f x) (* x x))).count_items(), 3);
+/// assert!(matches!(atom!([1 2]), Atom::Vector(_)));
+///
+/// assert!(atom!(set-car!).eq_identifier("set-car!"));
+/// assert!(atom!(define-record).eq_identifier("define-record"));
+/// assert!(atom!(null?).eq_identifier("null?"));
+/// assert!(atom!(->string).eq_identifier("->string"));
+/// assert_eq!(atom!(null? x), LispIter::new("(null? x)").next().unwrap());
+/// assert_eq!(atom!((set-car! p 1)).count_items(), 3);
+/// ```
+#[macro_export]
+macro_rules! atom {
+    ($($source:tt)*) => {{
+        let source = stringify!($($source)*);
+        let mut iter = $crate::LispIter::new(source);
+        match (iter.next(), iter.next()) {
+            (Some(atom), None) => atom,
+            _ => $crate::Atom::List($crate::LispIter::new(source)),
+        }
+    }};
+}
+
+#[cfg(test)]
+mod clone_tests {
+    use super::{Atom, LispIter};
+
+    fn identifiers(iter: LispIter<'_>) -> impl Iterator<Item = &str> {
+        iter.map(|atom| atom.as_identifier().expect("only identifiers expected"))
+    }
+
+    #[test]
+    fn clone_of_partly_consumed_iterator_is_independent() {
+        let mut original = LispIter::new("a b c d e");
+        original.next();
+        original.next();
+
+        let mut clone = original.clone();
+        assert!(clone.next().unwrap().eq_identifier("c"));
+        assert!(clone.next().unwrap().eq_identifier("d"));
+
+        assert!(identifiers(original.clone()).eq(["c", "d", "e"]));
+        assert!(identifiers(clone).eq(["e"]));
+        assert_eq!(original.byte_position(), 3);
+    }
+
+    #[test]
+    fn clone_of_nested_list_is_independent() {
+        let mut outer = LispIter::new("(a (b c (d e)) f) g");
+        let Some(Atom::List(mut list)) = outer.next() else {
+            panic!("expected a list");
+        };
+        assert!(list.next().unwrap().eq_identifier("a"));
+        let Some(Atom::List(mut nested)) = list.next() else {
+            panic!("expected a nested list");
+        };
+        nested.next();
+
+        let mut nested_clone = nested.clone();
+        assert!(nested_clone.next().unwrap().eq_identifier("c"));
+        let Some(Atom::List(deepest)) = nested_clone.next() else {
+            panic!("expected the innermost list");
+        };
+        assert!(identifiers(deepest).eq(["d", "e"]));
+        assert!(nested_clone.next().is_none());
+
+        assert!(nested.next().unwrap().eq_identifier("c"));
+        assert!(matches!(nested.next(), Some(Atom::List(_))));
+
+        let list_clone = list.clone();
+        assert!(identifiers(list_clone).eq(["f"]));
+        assert!(list.next().unwrap().eq_identifier("f"));
+        assert!(outer.next().unwrap().eq_identifier("g"));
+    }
+
+    #[test]
+    fn clones_of_the_same_atom_iterate_separately() {
+        let atom = LispIter::new("(x y z)").next().unwrap();
+        let (Atom::List(mut a), Atom::List(b)) = (atom.clone(), atom) else {
+            panic!("expected lists");
+        };
+        assert!(a.next().unwrap().eq_identifier("x"));
+        assert!(identifiers(b).eq(["x", "y", "z"]));
+        assert!(identifiers(a).eq(["y", "z"]));
+    }
+}
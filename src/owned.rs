@@ -0,0 +1,590 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    is_delimiter, parse_one, Atom, AtomKind, ConvertError, Event, LispIter, ParseError,
+    ParseOneError, Token, Walker,
+};
+
+/// An [`Atom`] that owns its contents instead of borrowing the source text, so it can
+/// outlive it. Requires the `alloc` feature.
+///
+/// Lists, vectors and maps hold their elements in a `Vec`, a map's keys and values
+/// alternating as in [`Atom::Map`]. Equality works as for [`Atom`], and `Debug` output is
+/// the same as for the atom it was made from.
+#[derive(Clone, PartialEq)]
+pub enum OwnedAtom {
+    Identifier(String),
+    Quote(String),
+    Integer(i64),
+    Float(f64),
+    Float32(f32),
+    Rational(i64, u64),
+    Bytes(Vec<u8>),
+    List(Vec<OwnedAtom>),
+    Vector(Vec<OwnedAtom>),
+    Map(Vec<OwnedAtom>),
+}
+
+impl OwnedAtom {
+    /// Returns which variant this atom is.
+    pub fn kind(&self) -> AtomKind {
+        match self {
+            Self::Identifier(_) => AtomKind::Identifier,
+            Self::Quote(_) => AtomKind::Quote,
+            Self::Integer(_) => AtomKind::Integer,
+            Self::Float(_) => AtomKind::Float,
+            Self::Float32(_) => AtomKind::Float32,
+            Self::Rational(..) => AtomKind::Rational,
+            Self::Bytes(_) => AtomKind::Bytes,
+            Self::List(_) => AtomKind::List,
+            Self::Vector(_) => AtomKind::Vector,
+            Self::Map(_) => AtomKind::Map,
+        }
+    }
+}
+
+impl Atom<'_> {
+    /// Copies the atom, and everything in it, into an [`OwnedAtom`]. Requires the `alloc`
+    /// feature.
+    ///
+    /// A list is parsed in full, on a clone of its iterator, so `self` isn't consumed. It's
+    /// walked without recursion, keeping a stack of the unfinished lists on the heap, so
+    /// deep nesting is fine.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, OwnedAtom};
+    ///
+    /// let owned = {
+    ///     let source = String::from("(point 1 2)");
+    ///     LispIter::new(&source).next().unwrap().clone_to_owned()
+    /// };
+    /// assert_eq!(owned.to_string(), "(point 1 2)");
+    ///
+    /// let deep = "(".repeat(2_000) + "x" + &")".repeat(2_000);
+    /// let mut owned = &LispIter::new(&deep).next().unwrap().clone_to_owned();
+    /// let mut depth = 0;
+    /// while let OwnedAtom::List(items) = owned {
+    ///     (owned, depth) = (&items[0], depth + 1);
+    /// }
+    /// assert_eq!((owned, depth), (&OwnedAtom::Identifier("x".into()), 2_000));
+    /// ```
+    pub fn clone_to_owned(&self) -> OwnedAtom {
+        to_owned_tree(self, |atom| match atom {
+            Atom::Identifier(s) => OwnedAtom::Identifier(String::from(*s)),
+            Atom::Quote(s) => OwnedAtom::Quote(String::from(*s)),
+            Atom::Integer(v) => OwnedAtom::Integer(*v),
+            Atom::Float(v) => OwnedAtom::Float(*v),
+            Atom::Float32(v) => OwnedAtom::Float32(*v),
+            Atom::Rational(n, d) => OwnedAtom::Rational(*n, *d),
+            Atom::Bytes(hex) => OwnedAtom::Bytes(Vec::from(*hex)),
+            Atom::List(_) | Atom::Vector(_) | Atom::Map(_) => unreachable!(),
+        })
+    }
+}
+
+/// Copies `atom` into an [`OwnedAtom`], with `leaf` copying everything but lists, vectors
+/// and maps.
+///
+/// Lists are walked with a [`Walker`], so only quote forms, which it passes as leaves,
+/// are recursed into.
+fn to_owned_tree(atom: &Atom<'_>, leaf: fn(&Atom<'_>) -> OwnedAtom) -> OwnedAtom {
+    let (Atom::List(iter) | Atom::Vector(iter) | Atom::Map(iter)) = atom else {
+        return leaf(atom);
+    };
+    let container = |kind, items| match kind {
+        AtomKind::Vector => OwnedAtom::Vector(items),
+        AtomKind::Map => OwnedAtom::Map(items),
+        _ => OwnedAtom::List(items),
+    };
+    let mut walker = Walker::new(iter.clone());
+    let mut stack = Vec::new();
+    let mut items = Vec::new();
+    while let Some(event) = walker.next_event() {
+        match event {
+            Event::Enter(list) => stack.push((list.kind(), core::mem::take(&mut items))),
+            Event::Leaf(atom) => items.push(to_owned_tree(&atom, leaf)),
+            Event::Exit => {
+                let (kind, outer) = stack.pop().expect("an exit matches an enter");
+                let list = container(kind, core::mem::replace(&mut items, outer));
+                items.push(list);
+            }
+        }
+    }
+    container(atom.kind(), items)
+}
+
+impl Atom<'_> {
+    /// Copies the atom into an [`OwnedAtom`] in canonical form, so that atoms written
+    /// differently but meaning the same compare equal. Requires the `alloc` feature.
+    ///
+    /// Numbers are already compared by value, `1.0` and `1.00` both being `Float(1.0)`, so
+    /// this only changes what parsing leaves apart:
+    ///
+    /// - a rational is reduced to lowest terms, and becomes an integer if its denominator
+    ///   reduces to 1,
+    /// - `-0.0` becomes `0.0`, also as an `f32`,
+    /// - byte strings use lowercase hex digits,
+    /// - lists, vectors and maps are normalized element by element, without recursion as
+    ///   in [`Atom::clone_to_owned`].
+    ///
+    /// Integers and floats never turn into one another, and identifiers and quotes are
+    /// copied as they are, escape sequences included.
+    ///
+    /// ```
+    /// use lisp_iter::{LispIter, OwnedAtom};
+    ///
+    /// let mut atoms = LispIter::new("(6/4 #\"CAFE\" -0.0) (3/2 #\"cafe\" 0.0)");
+    /// let (a, b) = (atoms.next().unwrap(), atoms.next().unwrap());
+    /// assert_ne!(a, b);
+    /// assert_eq!(a.normalize(), b.normalize());
+    /// assert_eq!(LispIter::new("8/4").next().unwrap().normalize(), OwnedAtom::Integer(2));
+    ///
+    /// let deep = "[".repeat(2_000) + "6/4 -0.0" + &"]".repeat(2_000);
+    /// let mut owned = &LispIter::new(&deep).next().unwrap().normalize();
+    /// let mut depth = 0;
+    /// while let OwnedAtom::Vector(items) = owned {
+    ///     (owned, depth) = (items.last().unwrap(), depth + 1);
+    /// }
+    /// assert_eq!(depth, 2_000);
+    /// assert!(matches!(owned, OwnedAtom::Float(zero) if zero.is_sign_positive()));
+    /// ```
+    pub fn normalize(&self) -> OwnedAtom {
+        to_owned_tree(self, |atom| match atom {
+            Atom::Float(v) if *v == 0.0 => OwnedAtom::Float(0.0),
+            Atom::Float32(v) if *v == 0.0 => OwnedAtom::Float32(0.0),
+            Atom::Rational(n, d) => {
+                let divisor = gcd(n.unsigned_abs(), *d);
+                // The quotient can't exceed `n` in magnitude, nor `i64::MIN` once negated.
+                let n = (*n as i128 / divisor as i128) as i64;
+                match d / divisor {
+                    1 => OwnedAtom::Integer(n),
+                    d => OwnedAtom::Rational(n, d),
+                }
+            }
+            Atom::Bytes(hex) => OwnedAtom::Bytes(hex.to_ascii_lowercase()),
+            _ => atom.clone_to_owned(),
+        })
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl From<Atom<'_>> for OwnedAtom {
+    fn from(atom: Atom<'_>) -> Self {
+        atom.clone_to_owned()
+    }
+}
+
+/// Borrows a non-container [`OwnedAtom`] as an [`Atom`].
+///
+/// An [`Atom::List`] is an iterator over source text, which an owned list doesn't have, so
+/// lists, vectors and maps fail with [`ConvertError::WrongKind`].
+impl<'a> TryFrom<&'a OwnedAtom> for Atom<'a> {
+    type Error = ConvertError;
+
+    fn try_from(atom: &'a OwnedAtom) -> Result<Self, Self::Error> {
+        Ok(match atom {
+            OwnedAtom::Identifier(s) => Atom::Identifier(s),
+            OwnedAtom::Quote(s) => Atom::Quote(s),
+            OwnedAtom::Integer(v) => Atom::Integer(*v),
+            OwnedAtom::Float(v) => Atom::Float(*v),
+            OwnedAtom::Float32(v) => Atom::Float32(*v),
+            OwnedAtom::Rational(n, d) => Atom::Rational(*n, *d),
+            OwnedAtom::Bytes(hex) => Atom::Bytes(hex),
+            OwnedAtom::List(_) | OwnedAtom::Vector(_) | OwnedAtom::Map(_) => {
+                return Err(ConvertError::WrongKind {
+                    found: atom.kind(),
+                    target: "Atom",
+                })
+            }
+        })
+    }
+}
+
+impl core::fmt::Debug for OwnedAtom {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Identifier(arg0) => f.debug_tuple("Identifier").field(arg0).finish(),
+            Self::Quote(arg0) => f.debug_tuple("Quote").field(arg0).finish(),
+            Self::Integer(arg0) => f.debug_tuple("Integer").field(arg0).finish(),
+            Self::Float(arg0) => f.debug_tuple("Float").field(arg0).finish(),
+            Self::Float32(arg0) => f.debug_tuple("Float32").field(arg0).finish(),
+            Self::Rational(n, d) => f.debug_tuple("Rational").field(n).field(d).finish(),
+            Self::Bytes(hex) => write!(f, "Bytes(\"{}\")", hex.escape_ascii()),
+            Self::List(arg0) | Self::Vector(arg0) => f.debug_list().entries(arg0).finish(),
+            Self::Map(arg0) => {
+                let pairs = arg0.chunks_exact(2).map(|pair| (&pair[0], &pair[1]));
+                f.debug_map().entries(pairs).finish()
+            }
+        }
+    }
+}
+
+/// Writes the atom back as source, on one line, like [`crate::PrettyPrinter`] does for
+/// short lists.
+impl core::fmt::Display for OwnedAtom {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (open, close, elements) = match self {
+            Self::Identifier(s) => return f.write_str(s),
+            Self::Quote(s) => return crate::write_quote(f, s),
+            Self::Integer(v) => return write!(f, "{}", v),
+            Self::Float(v) => return write!(f, "{:?}", v),
+            Self::Float32(v) => return write!(f, "{:?}f32", v),
+            Self::Rational(n, d) => return write!(f, "{}/{}", n, d),
+            Self::Bytes(hex) => return write!(f, "#\"{}\"", hex.escape_ascii()),
+            Self::List(elements) => ("(", ")", elements),
+            Self::Vector(elements) => ("[", "]", elements),
+            Self::Map(elements) => ("{", "}", elements),
+        };
+        f.write_str(open)?;
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}", element)?;
+        }
+        f.write_str(close)
+    }
+}
+
+/// Parses exactly one expression, as with [`parse_one`].
+///
+/// Whitespace and comments may surround it, but a second expression is
+/// [`ParseOwnedError::TrailingInput`] rather than ignored, so a string round-trips through
+/// `Display` and `parse`. Errors anywhere inside the expression are reported.
+///
+/// ```
+/// use lisp_iter::OwnedAtom;
+///
+/// let atom: OwnedAtom = "(a b c) ; trailing comment".parse().unwrap();
+/// assert_eq!(atom.to_string(), "(a b c)");
+/// assert!("(a) (b)".parse::<OwnedAtom>().is_err());
+///
+/// // `Display` writes a quote holding `"` as a raw string, and a keyword as a plain quote.
+/// for source in [r##"r#"a"b"#"##, r##"r#"say "hi""#"##, r###"r##"a"#b"##"###, ":key"] {
+///     let atom: OwnedAtom = source.parse().unwrap();
+///     assert_eq!(atom.to_string().parse::<OwnedAtom>().unwrap(), atom);
+/// }
+/// let atom: OwnedAtom = r#"(x "a b" :k)"#.parse().unwrap();
+/// assert_eq!(atom.to_string(), r#"(x "a b" "k")"#);
+/// let atom: OwnedAtom = r##"(x r#"a"b"# :k)"##.parse().unwrap();
+/// assert_eq!(atom.to_string(), r##"(x r#"a"b"# "k")"##);
+/// assert_eq!(atom.to_string().parse::<OwnedAtom>().unwrap(), atom);
+/// ```
+impl core::str::FromStr for OwnedAtom {
+    type Err = ParseOwnedError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(parse_one(s)?.clone_to_owned())
+    }
+}
+
+/// Error parsing an [`OwnedAtom`] from a string: a [`ParseOneError`] that doesn't borrow
+/// the input, as `FromStr` requires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseOwnedError {
+    /// The input holds no expression, only whitespace and comments.
+    Empty,
+    /// Something other than whitespace and comments follows the expression.
+    TrailingInput { offset: usize },
+    /// [`ParseError::InvalidNumber`], with the token copied out of the input.
+    InvalidNumber { offset: usize, token: String },
+    /// Any other [`ParseError`], none of which borrow the input.
+    Parse(ParseError<'static>),
+}
+
+impl From<ParseOneError<'_>> for ParseOwnedError {
+    fn from(e: ParseOneError<'_>) -> Self {
+        use ParseError::*;
+        let e = match e {
+            ParseOneError::Empty => return Self::Empty,
+            ParseOneError::TrailingInput { offset } => return Self::TrailingInput { offset },
+            ParseOneError::Parse(e) => e,
+        };
+        Self::Parse(match e {
+            InvalidNumber { offset, token } => {
+                let token = String::from(token);
+                return Self::InvalidNumber { offset, token };
+            }
+            IntegerOverflow { offset } => IntegerOverflow { offset },
+            UnclosedList { offset } => UnclosedList { offset },
+            UnclosedQuote { offset } => UnclosedQuote { offset },
+            InvalidBytes { offset } => InvalidBytes { offset },
+            UnclosedBlockComment { offset } => UnclosedBlockComment { offset },
+            OddMapLength { offset } => OddMapLength { offset },
+            UnexpectedCloser { offset } => UnexpectedCloser { offset },
+            MismatchedCloser {
+                offset,
+                expected,
+                found,
+            } => MismatchedCloser {
+                offset,
+                expected,
+                found,
+            },
+            UnexpectedAtom { expected, got } => UnexpectedAtom { expected, got },
+            UnexpectedEnd { expected } => UnexpectedEnd { expected },
+            ExpectedKeyword { offset } => ExpectedKeyword { offset },
+        })
+    }
+}
+
+impl core::fmt::Display for ParseOwnedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "{}", ParseOneError::Empty),
+            Self::TrailingInput { offset } => {
+                write!(f, "{}", ParseOneError::TrailingInput { offset: *offset })
+            }
+            Self::InvalidNumber { offset, token } => {
+                let token = token.as_str();
+                write!(
+                    f,
+                    "{}",
+                    ParseError::InvalidNumber {
+                        offset: *offset,
+                        token
+                    }
+                )
+            }
+            Self::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl core::error::Error for ParseOwnedError {}
+
+impl<'s> LispIter<'s> {
+    /// Consumes the iterator, collecting the names of its [`Atom::Identifier`]s. Requires
+    /// the `alloc` feature.
+    ///
+    /// Shorthand for `iter.identifiers().collect()`: like [`LispIter::identifiers`], only
+    /// atoms at this level are looked at, and others are skipped.
+    ///
+    /// ```
+    /// let params = lisp_iter::LispIter::new("(x y 1 \"z\" (w))").next().unwrap();
+    /// assert_eq!(params.into_list_iter().unwrap().collect_identifiers(), ["x", "y"]);
+    /// ```
+    pub fn collect_identifiers(self) -> Vec<&'s str> {
+        self.identifiers().collect()
+    }
+
+    /// Consumes the iterator, collecting all its atoms. Requires the `alloc` feature.
+    ///
+    /// Same as `collect::<Vec<_>>()`, except that the atoms are counted first, by a scan
+    /// that skips over lists without parsing them, so the `Vec` is allocated once at its
+    /// final size.
+    ///
+    /// ```
+    /// let atoms = lisp_iter::LispIter::new("(a b) c ; d").drain_into_vec();
+    /// assert_eq!(atoms.len(), 2);
+    /// ```
+    pub fn drain_into_vec(self) -> Vec<Atom<'s>> {
+        let mut atoms = Vec::with_capacity(self.clone().count_extents());
+        atoms.extend(self);
+        atoms
+    }
+
+    /// Consumes the iterator, collecting the contents of its [`Atom::Quote`]s. See
+    /// [`LispIter::collect_identifiers`].
+    pub fn collect_quotes(self) -> Vec<&'s str> {
+        self.quotes().collect()
+    }
+
+    /// Consumes the iterator, collecting the values of its [`Atom::Integer`]s. See
+    /// [`LispIter::collect_identifiers`].
+    pub fn collect_integers(self) -> Vec<i64> {
+        self.integers().collect()
+    }
+}
+
+impl<'s> LispIter<'s> {
+    /// Turns the iterator into one over every run of `size` consecutive atoms, like
+    /// `slice::windows`. Requires the `alloc` feature.
+    ///
+    /// Works like [`LispIter::windows_const`], parsing each window afresh, but with the
+    /// size chosen at run time and each window collected into a `Vec`.
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// ```
+    /// let pairs: Vec<_> = lisp_iter::LispIter::new("a b c").windows(2).collect();
+    /// assert_eq!(pairs.len(), 2);
+    /// assert!(pairs[1][0].eq_identifier("b") && pairs[1][1].eq_identifier("c"));
+    /// ```
+    pub fn windows(self, size: usize) -> Windows<'s> {
+        assert!(size > 0, "window size must be non-zero");
+        Windows { iter: self, size }
+    }
+}
+
+/// Iterator over runs of consecutive atoms, see [`LispIter::windows`].
+#[derive(Clone)]
+pub struct Windows<'s> {
+    iter: LispIter<'s>,
+    size: usize,
+}
+
+impl<'s> Iterator for Windows<'s> {
+    type Item = Vec<Atom<'s>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut fork = self.iter.clone();
+        let window: Vec<_> = fork.by_ref().take(self.size).collect();
+        if window.len() < self.size {
+            self.iter = fork;
+            return None;
+        }
+        self.iter.skip_expr();
+        Some(window)
+    }
+}
+
+impl LispIter<'_> {
+    /// Creates an iterator over the expressions read from any source of chars, such as a
+    /// decoded file or socket. Requires the `alloc` feature.
+    ///
+    /// There's no borrowed text for the atoms to point into, so they come out as
+    /// [`OwnedAtom`]s. Each call to `next` reads chars into a buffer only until an
+    /// expression is complete, then parses it from there with a [`LispIter`], so a source
+    /// that never ends, like a REPL's input, still yields every expression as it arrives.
+    ///
+    /// ```
+    /// use lisp_iter::LispIter;
+    ///
+    /// let mut iter = LispIter::new_from_chars("(a 1) b".chars());
+    /// assert_eq!(iter.next().unwrap().to_string(), "(a 1)");
+    /// assert_eq!(iter.next().unwrap().to_string(), "b");
+    /// assert!(iter.next().is_none());
+    ///
+    /// let mut read = 0;
+    /// let endless = "(a 1) \"b c\" ; d\n".chars().chain(std::iter::repeat('x'));
+    /// let mut iter = LispIter::new_from_chars(endless.inspect(|_| read += 1));
+    /// assert_eq!(iter.next().unwrap().to_string(), "(a 1)");
+    /// assert_eq!(iter.next().unwrap().to_string(), "\"b c\"");
+    /// drop(iter);
+    /// assert_eq!(read, 11);
+    /// ```
+    pub fn new_from_chars<I>(chars: I) -> GenericLispIter<I>
+    where
+        I: Iterator<Item = char>,
+    {
+        GenericLispIter {
+            chars: Some(chars),
+            buf: String::new(),
+            scanned: 0,
+            depth: 0,
+            pending: None,
+        }
+    }
+}
+
+/// Iterator over the expressions read from a char source, see
+/// [`LispIter::new_from_chars`]. Requires the `alloc` feature.
+pub struct GenericLispIter<I> {
+    /// The source, until it runs out.
+    chars: Option<I>,
+    /// Text read but not yet parsed, starting where the next expression does.
+    buf: String,
+    /// How much of `buf` has been lexed into finished tokens.
+    scanned: usize,
+    /// Brackets open at `scanned`.
+    depth: usize,
+    /// The unfinished token at `scanned`, if any.
+    pending: Option<Token>,
+}
+
+impl<I> GenericLispIter<I> {
+    /// Lexes the finished tokens of `buf` past `scanned`, returning whether they complete
+    /// an expression.
+    fn scan(&mut self) -> bool {
+        let mut iter = LispIter::new(&self.buf[self.scanned..]);
+        self.pending = None;
+        while let Some((token, span)) = iter.lex() {
+            let finished = match token {
+                Token::Word | Token::LineComment => span.end < iter.input.len(),
+                Token::Quote { closed, .. }
+                | Token::Bytes { closed }
+                | Token::BlockComment { closed } => closed,
+                Token::Open(_) | Token::Close(_) | Token::Apostrophe { .. } => true,
+            };
+            if !finished {
+                self.scanned += span.start;
+                self.pending = Some(token);
+                return false;
+            }
+            let complete = match token {
+                Token::Open(_) => {
+                    self.depth += 1;
+                    false
+                }
+                Token::Close(_) if self.depth > 0 => {
+                    self.depth -= 1;
+                    self.depth == 0
+                }
+                Token::Word | Token::Quote { .. } | Token::Bytes { .. } => self.depth == 0,
+                _ => false,
+            };
+            if complete {
+                self.scanned += span.end;
+                return true;
+            }
+        }
+        self.scanned = self.buf.len();
+        false
+    }
+
+    /// Whether reading `c` may finish the token at `scanned`.
+    fn may_finish(&self, c: char) -> bool {
+        match self.pending {
+            None | Some(Token::Word) => is_delimiter(c),
+            Some(Token::LineComment) => c == '\n',
+            Some(Token::Quote { raw: None, .. } | Token::Bytes { .. }) => c == '"',
+            Some(Token::Quote { raw: Some(_), .. }) => c == '"' || c == '#',
+            _ => c == '#',
+        }
+    }
+}
+
+impl<I> Iterator for GenericLispIter<I>
+where
+    I: Iterator<Item = char>,
+{
+    type Item = OwnedAtom;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut complete = self.scan();
+            while !complete {
+                let Some(c) = self.chars.as_mut().and_then(Iterator::next) else {
+                    self.chars = None;
+                    break;
+                };
+                self.buf.push(c);
+                if self.may_finish(c) {
+                    complete = self.scan();
+                }
+            }
+            let mut iter = LispIter::new(&self.buf);
+            // A complete token may still be skipped, like malformed bytes. Then the rest of
+            // the buffer has to wait for more input.
+            let Some(atom) = iter.next() else {
+                if self.chars.is_none() {
+                    self.buf.clear();
+                    return None;
+                }
+                continue;
+            };
+            let atom = atom.clone_to_owned();
+            let end = iter.byte_position();
+            self.buf.drain(..end);
+            (self.scanned, self.depth, self.pending) = (0, 0, None);
+            return Some(atom);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+//! Randomized checks of properties that should hold for any input, well-formed or not.
+//!
+//! Inputs come from a fixed-seed generator, so failures are reproducible; the failing
+//! input is part of each assertion message.
+
+use std::hash::{BuildHasher, RandomState};
+
+use std::ops::Range;
+
+use lisp_iter::{check, Atom, LispIter, ParseConfig, ParseError};
+
+/// Xorshift generator, good enough to vary test inputs.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+/// Tokens that inputs are made of, mostly well-formed atoms with some broken ones mixed in.
+const TOKENS: &[&str] = &[
+    "a",
+    "set-car!",
+    "12",
+    "-7",
+    "1.5",
+    "-0.0",
+    "0.0",
+    "nan",
+    "1e3",
+    "3/4",
+    "1/0",
+    "12abc",
+    "99999999999999999999",
+    "\"str\"",
+    "\"a b\"",
+    ":key",
+    "#\"CAFE\"",
+    "#\"cafe\"",
+    "#\"zz\"",
+    "r#\"raw\"#",
+    "'",
+    "'x",
+    ";c\n",
+    "#|c|#",
+    "/*c*/",
+];
+
+/// Builds a random input: atoms and brackets, mostly balanced, with the odd stray or
+/// unclosed one.
+fn input(rng: &mut Rng) -> String {
+    let mut out = String::new();
+    let mut open = Vec::new();
+    for _ in 0..rng.below(24) {
+        match rng.below(10) {
+            0..=1 => {
+                let (opener, closer) = [("(", ')'), ("[", ']'), ("{", '}')][rng.below(3)];
+                out.push_str(opener);
+                open.push(closer);
+            }
+            2 => match open.pop() {
+                Some(closer) => out.push(closer),
+                None => out.push_str(rng.pick(&[")", "]", "}"])),
+            },
+            _ => out.push_str(rng.pick(TOKENS)),
+        }
+        out.push_str(rng.pick(&[" ", " ", "\n", ""]));
+    }
+    while let Some(closer) = open.pop() {
+        if rng.below(8) > 0 {
+            out.push(closer);
+        }
+    }
+    out
+}
+
+/// The configs to run each input under.
+fn configs() -> impl Iterator<Item = ParseConfig> {
+    (0..16).map(|bits| ParseConfig {
+        strict_numbers: bits & 1 != 0,
+        c_comments: bits & 2 != 0,
+        exponent_integers: bits & 4 != 0,
+        quote_forms: bits & 8 != 0,
+    })
+}
+
+/// Runs `check` on many random inputs, under every config.
+fn for_inputs(mut check: impl FnMut(&str, ParseConfig)) {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    for _ in 0..2_000 {
+        let input = input(&mut rng);
+        for config in configs() {
+            check(&input, config);
+        }
+    }
+}
+
+#[test]
+fn nth_expr_matches_repeated_next() {
+    for_inputs(|input, config| {
+        let total = LispIter::with_config(input, config).count();
+        for n in 0..=total + 1 {
+            let mut manual = LispIter::with_config(input, config);
+            let expected = (0..n).try_for_each(|_| manual.next().map(drop));
+            let expected = expected.and_then(|()| manual.next());
+            let mut skipping = LispIter::with_config(input, config);
+            let actual = skipping.nth_expr(n);
+            assert_eq!(
+                actual, expected,
+                "nth_expr({n}) of {input:?} under {config:?}"
+            );
+            assert_eq!(
+                skipping.byte_position(),
+                manual.byte_position(),
+                "position after nth_expr({n}) of {input:?} under {config:?}"
+            );
+        }
+    });
+}
+
+#[test]
+fn skip_expr_matches_next() {
+    for_inputs(|input, config| {
+        let mut skipping = LispIter::with_config(input, config);
+        let mut parsing = LispIter::with_config(input, config);
+        loop {
+            let context = format!(
+                "at byte {} of {input:?} under {config:?}",
+                parsing.byte_position()
+            );
+            let skipped = skipping.skip_expr();
+            assert_eq!(skipped, parsing.next().is_some(), "skip_expr {context}");
+            assert_eq!(
+                skipping.byte_position(),
+                parsing.byte_position(),
+                "position {context}"
+            );
+            if !skipped {
+                break;
+            }
+        }
+
+        let total = LispIter::with_config(input, config).count();
+        for n in 0..=total + 1 {
+            let mut skipping = LispIter::with_config(input, config);
+            let mut parsing = LispIter::with_config(input, config);
+            let parsed = parsing.by_ref().take(n).count();
+            assert_eq!(
+                skipping.skip_exprs(n),
+                parsed,
+                "skip_exprs({n}) of {input:?}"
+            );
+            assert_eq!(
+                skipping.byte_position(),
+                parsing.byte_position(),
+                "skip_exprs({n}) of {input:?}"
+            );
+            assert_eq!(
+                skipping.next(),
+                parsing.next(),
+                "after skip_exprs({n}) of {input:?}"
+            );
+        }
+    });
+}
+
+/// Every atom below `iter` in source order, as its kind or leaf value with its span.
+fn spans(mut iter: LispIter<'_>) -> Vec<(String, Range<usize>, Range<usize>)> {
+    let mut out = Vec::new();
+    while let Some((atom, span)) = iter.next_spanned() {
+        let kind = atom.kind();
+        match atom {
+            Atom::List(inner) | Atom::Vector(inner) | Atom::Map(inner) => {
+                out.push((format!("{kind:?}"), span.outer, span.inner));
+                out.extend(spans(inner));
+            }
+            leaf => out.push((format!("{leaf:?}"), span.outer, span.inner)),
+        }
+    }
+    out
+}
+
+#[test]
+fn prefixed_matches_concatenation() {
+    const PREFIXES: &[&str] = &["", "(begin", "(begin ", "(let ((x 1)) ", "[", "a (b) "];
+    const SUFFIXES: &[&str] = &["", ")", "))", " x)", "]", "; end", "#|c|#)"];
+    let mut rng = Rng(0xD1B5_4A32_D192_ED03);
+    for _ in 0..5_000 {
+        let input = input(&mut rng);
+        // A string or comment left open in the input would run on into the suffix, whose
+        // text the concatenation would then hold.
+        let unclosed = check(&input).any(|diagnostic| {
+            matches!(
+                diagnostic.error,
+                ParseError::UnclosedQuote { .. } | ParseError::UnclosedBlockComment { .. }
+            )
+        });
+        if unclosed {
+            continue;
+        }
+        let (prefix, suffix) = (rng.pick(PREFIXES), rng.pick(SUFFIXES));
+        let lead = match prefix {
+            "" => String::new(),
+            prefix => format!("{prefix}\n"),
+        };
+        let joined = match suffix {
+            "" => format!("{lead}{input}"),
+            suffix => format!("{lead}{input}\n{suffix}"),
+        };
+        let clamp = |offset: usize| offset.saturating_sub(lead.len()).min(input.len());
+        let clamp_range = |range: Range<usize>| clamp(range.start)..clamp(range.end);
+        let context = format!("{prefix:?} + {input:?} + {suffix:?}");
+
+        let expected: Vec<_> = spans(LispIter::new(&joined))
+            .into_iter()
+            .map(|(atom, outer, inner)| (atom, clamp_range(outer), clamp_range(inner)))
+            .collect();
+        let mut prefixed = LispIter::new_prefixed(prefix, &input, suffix);
+        assert_eq!(spans(prefixed.clone()), expected, "{context}");
+
+        let mut plain = LispIter::new(&joined);
+        while prefixed.next().is_some() {
+            plain.next();
+            let position = clamp(plain.byte_position());
+            assert_eq!(prefixed.byte_position(), position, "{context}");
+        }
+        assert!(plain.next().is_none(), "{context}");
+    }
+}
+
+#[test]
+fn equal_atoms_hash_equal() {
+    const LEAVES: &[&str] = &[
+        "0.0",
+        "-0.0",
+        "0.00",
+        "-nan",
+        "+nan",
+        "1.5",
+        "1.50",
+        "-inf",
+        "0.0f32",
+        "-0.0f32",
+        "1",
+        "01",
+        "#\"CAFE\"",
+        "#\"cafe\"",
+        "#\"CaFe\"",
+        "a",
+        "\"a\"",
+        ":a",
+        "1/2",
+        "2/4",
+    ];
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    let mut sources: Vec<String> = LEAVES.iter().map(|leaf| leaf.to_string()).collect();
+    for _ in 0..300 {
+        let (opener, closer) = [("(", ")"), ("[", "]"), ("{", "}")][rng.below(3)];
+        let mut source = String::from(opener);
+        for _ in 0..2 * rng.below(3) {
+            source += rng.pick(LEAVES);
+            source += rng.pick(&[" ", " ;c\n", " #|c|# "]);
+        }
+        source += closer;
+        sources.push(source);
+    }
+    let atoms: Vec<Atom<'_>> = sources
+        .iter()
+        .map(|s| LispIter::new(s).next().unwrap())
+        .collect();
+
+    let hasher = RandomState::new();
+    let mut equal_pairs = 0;
+    for (a, source_a) in atoms.iter().zip(&sources) {
+        for (b, source_b) in atoms.iter().zip(&sources) {
+            if a == b {
+                equal_pairs += 1;
+                let context = format!("{source_a:?} == {source_b:?}");
+                assert_eq!(hasher.hash_one(a), hasher.hash_one(b), "{context}");
+            }
+        }
+    }
+    assert!(
+        equal_pairs > atoms.len(),
+        "too few distinct equal pairs to mean anything"
+    );
+
+    let atom = |source| LispIter::new(source).next().unwrap();
+    assert_eq!(atom("0.0"), atom("-0.0"));
+    assert_eq!(hasher.hash_one(atom("0.0")), hasher.hash_one(atom("-0.0")));
+    assert_eq!(
+        hasher.hash_one(atom("[-0.0f32]")),
+        hasher.hash_one(atom("[0.0f32]"))
+    );
+    assert_ne!(atom("-nan"), atom("-nan"));
+    assert_eq!(hasher.hash_one(atom("-nan")), hasher.hash_one(atom("-nan")));
+    assert_ne!(atom("#\"CAFE\""), atom("#\"cafe\""));
+}